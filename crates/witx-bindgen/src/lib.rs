@@ -0,0 +1,358 @@
+//! Generator of the `src/generated_*.rs` host function bindings.
+//!
+//! Every witx file of the `witx` directory describes one host module. The
+//! generated code is formatted with `rustfmt`, which must be installed.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use witx::{BuiltinType, Document, Id, InterfaceFunc, NamedType, RecordDatatype, Type, TypeRef};
+
+// The witx definitions and the bindings generated from them, relative to the
+// repository root.
+pub const BINDINGS: [(&str, &str); 4] = [
+    ("witx/wasmedge_tensorflow.witx", "src/generated_tf.rs"),
+    (
+        "witx/wasmedge_tensorflowlite.witx",
+        "src/generated_tflite.rs",
+    ),
+    ("witx/wasmedge_image.witx", "src/generated_img.rs"),
+    ("witx/wasi_ephemeral_nn.witx", "src/generated_nn.rs"),
+];
+
+// The repository root.
+pub fn root_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../..")
+}
+
+// Generate the formatted bindings of a witx file.
+pub fn generate(witx_path: &Path) -> Result<String, String> {
+    let doc = witx::load(&[witx_path]).map_err(|e| e.to_string())?;
+    rustfmt(&render(&doc))
+}
+
+fn rustfmt(code: &str) -> Result<String, String> {
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", "2021", "--emit", "stdout", "--quiet"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("cannot run rustfmt: {}", e))?;
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(code.as_bytes())
+        .map_err(|e| e.to_string())?;
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err("rustfmt failed on the generated code".to_string());
+    }
+    String::from_utf8(output.stdout).map_err(|e| e.to_string())
+}
+
+fn render(doc: &Document) -> String {
+    let error_types: Vec<Id> = doc
+        .error_types()
+        .filter_map(|t| match t {
+            TypeRef::Name(nt) => Some(nt.name.clone()),
+            TypeRef::Value(_) => None,
+        })
+        .collect();
+    let funcs: Vec<(Id, std::rc::Rc<InterfaceFunc>)> = doc
+        .modules()
+        .flat_map(|m| m.funcs().map(|f| (m.name.clone(), f)).collect::<Vec<_>>())
+        .collect();
+
+    let mut src = String::new();
+    src.push_str("// This file is automatically generated, DO NOT EDIT\n");
+    src.push_str("//\n");
+    src.push_str("// To regenerate this file run the `crates/witx-bindgen` command\n\n");
+    if doc.typenames().any(|nt| is_enum(&nt.tref)) {
+        src.push_str("use core::fmt;\n");
+    }
+    if funcs.iter().any(|(_, f)| result_types(f).0.is_some()) {
+        src.push_str("use core::mem::MaybeUninit;\n");
+    }
+
+    let mut std_declared = false;
+    for nt in doc.typenames() {
+        if is_enum(&nt.tref) {
+            let is_error = error_types.contains(&nt.name);
+            render_enum(&mut src, &nt, is_error && !std_declared);
+            std_declared |= is_error;
+        } else if let Type::Record(r) = &**nt.type_() {
+            render_record(&mut src, &nt, r);
+        } else {
+            let (lifetime, ty) = if has_lifetime(&nt.tref) {
+                ("<'a>", rust_type(nt.type_(), "'a"))
+            } else {
+                ("", rust_type(nt.type_(), ""))
+            };
+            src.push_str(&format!(
+                "pub type {}{} = {};\n",
+                camel(&nt.name),
+                lifetime,
+                ty
+            ));
+        }
+    }
+
+    for (module, f) in &funcs {
+        render_func(&mut src, module, f);
+    }
+
+    for m in doc.modules() {
+        src.push_str(&format!("pub mod {} {{\n", m.name.as_str()));
+        src.push_str(&format!(
+            "    #[link(wasm_import_module = \"{}\")]\n",
+            m.name.as_str()
+        ));
+        src.push_str("    extern \"C\" {\n");
+        for f in m.funcs() {
+            let args: Vec<String> = (0..wasm_arg_count(&f))
+                .map(|i| format!("arg{}: i32", i))
+                .collect();
+            src.push_str(&format!(
+                "        pub fn {}({}) -> i32;\n",
+                f.name.as_str(),
+                args.join(", ")
+            ));
+        }
+        src.push_str("    }\n}\n");
+    }
+    src
+}
+
+fn render_enum(src: &mut String, nt: &NamedType, with_error_impl: bool) {
+    let name = camel(&nt.name);
+    let prefix = nt.name.as_str().to_uppercase();
+    let cases = match &**nt.type_() {
+        Type::Variant(v) => &v.cases,
+        _ => unreachable!(),
+    };
+    src.push_str("#[repr(transparent)]\n");
+    src.push_str("#[derive(Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]\n");
+    src.push_str(&format!("pub struct {}(u32);\n", name));
+    for (i, c) in cases.iter().enumerate() {
+        src.push_str(&format!(
+            "pub const {}_{}: {} = {}({});\n",
+            prefix,
+            c.name.as_str().to_uppercase(),
+            name,
+            name,
+            i
+        ));
+    }
+    src.push_str(&format!("impl {} {{\n", name));
+    src.push_str("    pub const fn raw(&self) -> u32 {\n        self.0\n    }\n\n");
+    src.push_str("    pub fn name(&self) -> &'static str {\n        match self.0 {\n");
+    for (i, c) in cases.iter().enumerate() {
+        src.push_str(&format!(
+            "            {} => \"{}\",\n",
+            i,
+            c.name.as_str().to_uppercase()
+        ));
+    }
+    // Newer plug-ins may return codes unknown to the bindings.
+    src.push_str("            _ => \"UNKNOWN\",\n");
+    src.push_str("        }\n    }\n");
+    src.push_str("    pub fn message(&self) -> &'static str {\n        match self.0 {\n");
+    for (i, c) in cases.iter().enumerate() {
+        src.push_str(&format!("            {} => \"{}\",\n", i, c.docs.trim()));
+    }
+    src.push_str("            _ => \"\",\n");
+    src.push_str("        }\n    }\n}\n");
+    src.push_str(&format!("impl fmt::Debug for {} {{\n", name));
+    src.push_str("    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {\n");
+    src.push_str(&format!("        f.debug_struct(\"{}\")\n", name));
+    src.push_str("            .field(\"code\", &self.0)\n");
+    src.push_str("            .field(\"name\", &self.name())\n");
+    src.push_str("            .field(\"message\", &self.message())\n");
+    src.push_str("            .finish()\n    }\n}\n");
+    if with_error_impl {
+        src.push_str(&format!("impl fmt::Display for {} {{\n", name));
+        src.push_str("    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {\n");
+        src.push_str("        write!(f, \"{} (error {})\", self.name(), self.0)\n    }\n}\n\n");
+        src.push_str("#[cfg(feature = \"std\")]\nextern crate std;\n");
+        src.push_str(&format!(
+            "#[cfg(feature = \"std\")]\nimpl std::error::Error for {} {{}}\n",
+            name
+        ));
+    }
+    src.push('\n');
+}
+
+fn render_record(src: &mut String, nt: &NamedType, record: &RecordDatatype) {
+    let lifetime = if has_lifetime(&nt.tref) { "'a" } else { "" };
+    src.push_str("#[repr(C)]\n");
+    src.push_str("#[derive(Copy, Clone, Debug)]\n");
+    if lifetime.is_empty() {
+        src.push_str(&format!("pub struct {} {{\n", camel(&nt.name)));
+    } else {
+        src.push_str(&format!(
+            "pub struct {}<{}> {{\n",
+            camel(&nt.name),
+            lifetime
+        ));
+    }
+    for m in &record.members {
+        src.push_str(&format!(
+            "    pub {}: {},\n",
+            m.name.as_str(),
+            type_ref(&m.tref, lifetime)
+        ));
+    }
+    src.push_str("}\n");
+}
+
+fn render_func(src: &mut String, module: &Id, f: &InterfaceFunc) {
+    let (ok, err) = result_types(f);
+    let ok_ty = ok
+        .map(|t| type_ref(t, ""))
+        .unwrap_or_else(|| "()".to_string());
+    let err_ty = err
+        .map(|t| type_ref(t, ""))
+        .expect("functions must return an error type");
+
+    let mut params = Vec::new();
+    let mut args = Vec::new();
+    for p in &f.params {
+        let name = p.name.as_str();
+        params.push(format!("{}: {}", name, type_ref(&p.tref, "'_")));
+        match &**p.tref.type_() {
+            Type::List(_) => {
+                args.push(format!("{}.as_ptr() as i32", name));
+                args.push(format!("{}.len() as i32", name));
+            }
+            Type::Variant(_) => args.push(format!("{}.0 as i32", name)),
+            Type::Record(_) => args.push(format!("&{} as *const _ as i32", name)),
+            _ => args.push(format!("{} as i32", name)),
+        }
+    }
+    if ok.is_some() {
+        args.push("rp0.as_mut_ptr() as i32".to_string());
+    }
+
+    src.push_str(&format!(
+        "pub unsafe fn {}({}) -> Result<{}, {}> {{\n",
+        f.name.as_str(),
+        params.join(", "),
+        ok_ty,
+        err_ty
+    ));
+    if ok.is_some() {
+        src.push_str(&format!(
+            "    let mut rp0 = MaybeUninit::<{}>::uninit();\n",
+            ok_ty
+        ));
+    }
+    src.push_str(&format!(
+        "    let ret = {}::{}({});\n",
+        module.as_str(),
+        f.name.as_str(),
+        args.join(", ")
+    ));
+    src.push_str("    match ret {\n");
+    if ok.is_some() {
+        src.push_str(&format!(
+            "        0 => Ok(core::ptr::read(rp0.as_mut_ptr() as i32 as *const {})),\n",
+            ok_ty
+        ));
+    } else {
+        src.push_str("        0 => Ok(()),\n");
+    }
+    src.push_str(&format!("        _ => Err({}(ret as u32)),\n", err_ty));
+    src.push_str("    }\n}\n\n");
+}
+
+// The ok and error types of the single `expected` result of a function.
+fn result_types(f: &InterfaceFunc) -> (Option<&TypeRef>, Option<&TypeRef>) {
+    match f.results.first().map(|r| &**r.tref.type_()) {
+        Some(Type::Variant(v)) => v.as_expected().expect("results must be `expected`"),
+        _ => panic!("function `{}` must return an `expected`", f.name.as_str()),
+    }
+}
+
+fn wasm_arg_count(f: &InterfaceFunc) -> usize {
+    let params: usize = f
+        .params
+        .iter()
+        .map(|p| match &**p.tref.type_() {
+            Type::List(_) => 2,
+            _ => 1,
+        })
+        .sum();
+    params + result_types(f).0.is_some() as usize
+}
+
+fn is_enum(tref: &TypeRef) -> bool {
+    match &**tref.type_() {
+        Type::Variant(v) => v.is_enum() && v.as_expected().is_none(),
+        _ => false,
+    }
+}
+
+fn has_lifetime(tref: &TypeRef) -> bool {
+    match &**tref.type_() {
+        Type::List(_) => true,
+        Type::Record(r) => r.members.iter().any(|m| has_lifetime(&m.tref)),
+        _ => false,
+    }
+}
+
+fn type_ref(tref: &TypeRef, lifetime: &str) -> String {
+    match tref {
+        TypeRef::Name(nt) if has_lifetime(tref) => format!("{}<{}>", camel(&nt.name), lifetime),
+        TypeRef::Name(nt) => camel(&nt.name),
+        TypeRef::Value(t) => rust_type(t, lifetime),
+    }
+}
+
+fn rust_type(t: &Type, lifetime: &str) -> String {
+    // Inline references of parameters use elided lifetimes.
+    let reference = match lifetime {
+        "" | "'_" => "&".to_string(),
+        lifetime => format!("&{} ", lifetime),
+    };
+    match t {
+        Type::List(elem) => match &**elem.type_() {
+            Type::Builtin(BuiltinType::Char) => format!("{}str", reference),
+            _ => format!("{}[{}]", reference, type_ref(elem, lifetime)),
+        },
+        Type::Pointer(elem) => format!("*mut {}", type_ref(elem, lifetime)),
+        Type::ConstPointer(elem) => format!("*const {}", type_ref(elem, lifetime)),
+        Type::Handle(_) => "u32".to_string(),
+        Type::Builtin(b) => builtin(b).to_string(),
+        t => panic!("unsupported witx type: {}", t.kind()),
+    }
+}
+
+fn builtin(b: &BuiltinType) -> &'static str {
+    match b {
+        BuiltinType::Char => "char",
+        BuiltinType::U8 { .. } => "u8",
+        BuiltinType::U16 => "u16",
+        BuiltinType::U32 { .. } => "u32",
+        BuiltinType::U64 => "u64",
+        BuiltinType::S8 => "i8",
+        BuiltinType::S16 => "i16",
+        BuiltinType::S32 => "i32",
+        BuiltinType::S64 => "i64",
+        BuiltinType::F32 => "f32",
+        BuiltinType::F64 => "f64",
+    }
+}
+
+fn camel(id: &Id) -> String {
+    id.as_str()
+        .split('_')
+        .map(|w| {
+            let mut c = w.chars();
+            match c.next() {
+                Some(first) => first.to_uppercase().chain(c).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,26 @@
+// Regenerate the host function bindings from the witx definitions:
+//
+//     cargo run -p witx-bindgen
+
+use std::fs;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let root = witx_bindgen::root_dir();
+    for (witx, rs) in witx_bindgen::BINDINGS {
+        match witx_bindgen::generate(&root.join(witx)) {
+            Ok(code) => {
+                if let Err(e) = fs::write(root.join(rs), code) {
+                    eprintln!("{}: {}", rs, e);
+                    return ExitCode::FAILURE;
+                }
+                println!("generated {} from {}", rs, witx);
+            }
+            Err(e) => {
+                eprintln!("{}: {}", witx, e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    ExitCode::SUCCESS
+}
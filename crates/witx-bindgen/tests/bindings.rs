@@ -0,0 +1,18 @@
+use std::fs;
+
+// The committed bindings must match the witx definitions.
+// Run `cargo run -p witx-bindgen` after changing a witx file.
+#[test]
+fn bindings_match_witx() {
+    let root = witx_bindgen::root_dir();
+    for (witx, rs) in witx_bindgen::BINDINGS {
+        let generated = witx_bindgen::generate(&root.join(witx)).unwrap();
+        let committed = fs::read_to_string(root.join(rs)).unwrap();
+        assert!(
+            generated == committed,
+            "{} is out of date with {}, run `cargo run -p witx-bindgen`",
+            rs,
+            witx
+        );
+    }
+}
@@ -0,0 +1,289 @@
+// Command-line plumbing shared by the `tfrun` and `tfbench` examples: option
+// parsing, the element types they handle, image decoding and model loading.
+//
+// Each example uses a part of this module.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+use wasmedge_tensorflow_interface as tf;
+use wasmedge_tensorflow_interface::error::{self, Error};
+use wasmedge_tensorflow_interface::record::Backend;
+use wasmedge_tensorflow_interface::{DataType, TFLiteSession, TFSession, TensorType};
+
+// Errors of the crate, of the command line and of the files all end up as
+// the message of `main`.
+pub type Result<T, E = Box<dyn std::error::Error>> = std::result::Result<T, E>;
+
+// A tensor of one of the element types the examples handle.
+pub enum Tensor {
+    F32(Vec<f32>),
+    U8(Vec<u8>),
+    I32(Vec<i32>),
+    I64(Vec<i64>),
+}
+
+impl Tensor {
+    // A zero-filled tensor of `len` elements of type `f32`, `u8`, `i32` or `i64`.
+    pub fn zeros(dtype: DataType, len: usize) -> error::Result<Tensor> {
+        match dtype {
+            DataType::Float => Ok(Tensor::F32(vec![0.0; len])),
+            DataType::UInt8 => Ok(Tensor::U8(vec![0; len])),
+            DataType::Int32 => Ok(Tensor::I32(vec![0; len])),
+            DataType::Int64 => Ok(Tensor::I64(vec![0; len])),
+            _ => Err(Error::Unsupported("element type of the examples")),
+        }
+    }
+
+    // Read the output `name` as elements of type `f32`, `u8`, `i32` or `i64`.
+    pub fn read<B: Backend>(session: &B, name: &str, dtype: DataType) -> error::Result<Tensor> {
+        match dtype {
+            DataType::Float => Ok(Tensor::F32(session.get_output(name))),
+            DataType::UInt8 => Ok(Tensor::U8(session.get_output(name))),
+            DataType::Int32 => Ok(Tensor::I32(session.get_output(name))),
+            DataType::Int64 => Ok(Tensor::I64(session.get_output(name))),
+            _ => Err(Error::Unsupported("element type of the examples")),
+        }
+    }
+
+    // Set the tensor as the input `name` of a session.
+    pub fn add_to<B: Backend>(&self, session: &mut B, name: &str, shape: &[u64]) {
+        match self {
+            Tensor::F32(v) => session.add_input(name, v, shape),
+            Tensor::U8(v) => session.add_input(name, v, shape),
+            Tensor::I32(v) => session.add_input(name, v, shape),
+            Tensor::I64(v) => session.add_input(name, v, shape),
+        };
+    }
+
+    pub fn to_f32(&self) -> Vec<f32> {
+        match self {
+            Tensor::F32(v) => v.clone(),
+            Tensor::U8(v) => v.iter().map(|x| *x as f32).collect(),
+            Tensor::I32(v) => v.iter().map(|x| *x as f32).collect(),
+            Tensor::I64(v) => v.iter().map(|x| *x as f32).collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Tensor::F32(v) => v.len(),
+            Tensor::U8(v) => v.len(),
+            Tensor::I32(v) => v.len(),
+            Tensor::I64(v) => v.len(),
+        }
+    }
+}
+
+// A TensorFlow or TensorFlow-Lite session, picked from the model path.
+pub enum Session {
+    TF(TFSession),
+    TFLite(TFLiteSession),
+}
+
+impl Backend for Session {
+    fn add_input<T: TensorType>(&mut self, name: &str, data: &[T], shape: &[u64]) -> &mut Self {
+        match self {
+            Session::TF(s) => {
+                Backend::add_input(s, name, data, shape);
+            }
+            Session::TFLite(s) => {
+                Backend::add_input(s, name, data, shape);
+            }
+        }
+        self
+    }
+
+    fn clear_input(&mut self) -> &mut Self {
+        match self {
+            Session::TF(s) => {
+                Backend::clear_input(s);
+            }
+            Session::TFLite(s) => {
+                Backend::clear_input(s);
+            }
+        }
+        self
+    }
+
+    fn add_output(&mut self, name: &str) -> &mut Self {
+        match self {
+            Session::TF(s) => {
+                Backend::add_output(s, name);
+            }
+            Session::TFLite(s) => {
+                Backend::add_output(s, name);
+            }
+        }
+        self
+    }
+
+    fn run(&mut self) -> &mut Self {
+        match self {
+            Session::TF(s) => {
+                Backend::run(s);
+            }
+            Session::TFLite(s) => {
+                Backend::run(s);
+            }
+        }
+        self
+    }
+
+    fn get_output<T: TensorType>(&self, name: &str) -> Vec<T> {
+        match self {
+            Session::TF(s) => Backend::get_output(s, name),
+            Session::TFLite(s) => Backend::get_output(s, name),
+        }
+    }
+
+    fn get_output_shape(&self, name: &str) -> error::Result<Vec<u64>> {
+        match self {
+            Session::TF(s) => Backend::get_output_shape(s, name),
+            Session::TFLite(s) => Backend::get_output_shape(s, name),
+        }
+    }
+}
+
+// Command-line options, by name. Repeated options keep every value.
+pub struct Args {
+    pub model: String,
+    options: HashMap<String, Vec<String>>,
+}
+
+impl Args {
+    // Parse the command line. `flags` are the options without a value.
+    pub fn parse(usage: &str, flags: &[&str]) -> Result<Args, String> {
+        let mut model = None;
+        let mut options: HashMap<String, Vec<String>> = HashMap::new();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some("help") => return Err(usage.to_string()),
+                Some(name) if flags.contains(&name) => {
+                    options.entry(name.to_string()).or_default();
+                }
+                Some(name) => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("missing value of --{}", name))?;
+                    options.entry(name.to_string()).or_default().push(value);
+                }
+                None if model.is_none() => model = Some(arg),
+                None => return Err(format!("unexpected argument `{}`\n\n{}", arg, usage)),
+            }
+        }
+        Ok(Args {
+            model: model.ok_or_else(|| usage.to_string())?,
+            options,
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.options
+            .get(name)
+            .and_then(|v| v.last())
+            .map(String::as_str)
+    }
+
+    pub fn all(&self, name: &str, default: &str) -> Vec<String> {
+        match self.options.get(name) {
+            Some(values) => values.clone(),
+            None => vec![default.to_string()],
+        }
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+
+    pub fn parsed<T: std::str::FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        match self.get(name) {
+            Some(v) => v.parse().map_err(|_| format!("invalid --{} `{}`", name, v)),
+            None => Ok(default),
+        }
+    }
+
+    // An element type option: f32, u8, i32 or i64, by default f32.
+    pub fn element_type(&self, name: &str) -> Result<DataType, String> {
+        match self.get(name).unwrap_or("f32") {
+            "f32" => Ok(DataType::Float),
+            "u8" => Ok(DataType::UInt8),
+            "i32" => Ok(DataType::Int32),
+            "i64" => Ok(DataType::Int64),
+            t => Err(format!("invalid --{} `{}`", name, t)),
+        }
+    }
+
+    // The `--image-size` option as a width and a height.
+    pub fn image_size(&self) -> Result<(u32, u32), String> {
+        let size = self.get("image-size").unwrap_or("224x224");
+        size.split_once('x')
+            .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+            .ok_or_else(|| format!("invalid --image-size `{}`", size))
+    }
+}
+
+pub fn parse_shape(text: &str) -> Result<Vec<u64>, String> {
+    text.split(',')
+        .map(|d| {
+            d.trim()
+                .parse()
+                .map_err(|_| format!("invalid shape `{}`", text))
+        })
+        .collect()
+}
+
+// An image decoder for the `--image-type` pixels.
+pub type Decoder = fn(&[u8], u32, u32) -> Tensor;
+
+pub fn decoder(args: &Args, png: bool) -> Result<Decoder, String> {
+    let decode: Decoder = match (args.get("image-type").unwrap_or("rgb32f"), png) {
+        ("rgb32f", false) => |b, w, h| Tensor::F32(tf::load_jpg_image_to_rgb32f(b, w, h)),
+        ("bgr32f", false) => |b, w, h| Tensor::F32(tf::load_jpg_image_to_bgr32f(b, w, h)),
+        ("rgb8", false) => |b, w, h| Tensor::U8(tf::load_jpg_image_to_rgb8(b, w, h)),
+        ("bgr8", false) => |b, w, h| Tensor::U8(tf::load_jpg_image_to_bgr8(b, w, h)),
+        ("rgb32f", true) => |b, w, h| Tensor::F32(tf::load_png_image_to_rgb32f(b, w, h)),
+        ("bgr32f", true) => |b, w, h| Tensor::F32(tf::load_png_image_to_bgr32f(b, w, h)),
+        ("rgb8", true) => |b, w, h| Tensor::U8(tf::load_png_image_to_rgb8(b, w, h)),
+        ("bgr8", true) => |b, w, h| Tensor::U8(tf::load_png_image_to_bgr8(b, w, h)),
+        (t, _) => return Err(format!("invalid --image-type `{}`", t)),
+    };
+    Ok(decode)
+}
+
+// Load the model with the `--tag`, `--input` and `--output` options.
+pub fn open_session(args: &Args) -> Result<Session, String> {
+    let model = &args.model;
+    if Path::new(model).is_dir() {
+        let tags = args.all("tag", "serve");
+        let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+        return Ok(Session::TF(TFSession::new_from_saved_model(model, &tags)));
+    }
+    let buf = fs::read(model).map_err(|e| format!("{}: {}", model, e))?;
+    if model.ends_with(".tflite") {
+        Ok(Session::TFLite(TFLiteSession::new(buf)))
+    } else {
+        Ok(Session::TF(TFSession::new(buf)))
+    }
+}
+
+// Parse the command line and run an example, printing its errors.
+pub fn main(usage: &str, flags: &[&str], run: fn(&Args) -> Result<()>) -> ExitCode {
+    let args = match Args::parse(usage, flags) {
+        Ok(args) => args,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(msg) => {
+            eprintln!("error: {}", msg);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,135 @@
+// Benchmark a TensorFlow or TensorFlow-Lite model, timing every phase of the
+// session lifecycle: image decoding, `add_input`, `run` and `get_output`.
+//
+//     cargo build --release --target wasm32-wasi --example tfbench
+//     wasmedge --dir .:. target/wasm32-wasi/release/examples/tfbench.wasm \
+//         mobilenet_v2.tflite --image grace_hopper.jpg --image-type rgb8 \
+//         --output MobilenetV2/Predictions/Softmax --iterations 200 --json
+//
+// The host functions only exist in WasmEdge, so native builds only print a hint.
+#![cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+
+mod common;
+
+use common::{decoder, open_session, parse_shape, Args, Result, Tensor};
+use std::fs;
+use std::process::ExitCode;
+use std::time::Instant;
+use wasmedge_tensorflow_interface::profile::Profiler;
+use wasmedge_tensorflow_interface::record::Backend;
+
+const USAGE: &str = "usage: tfbench <MODEL> [OPTIONS]
+
+MODEL is a frozen graph (.pb), a SavedModel directory or a TensorFlow-Lite model (.tflite).
+
+options:
+  --image <FILE>          decode this JPEG or PNG image as the input of every iteration
+  --image-size <WxH>      image resize [default: 224x224]
+  --image-type <TYPE>     image pixels: rgb32f, bgr32f, rgb8 or bgr8 [default: rgb32f]
+  --shape <D0,D1,...>     input shape, filled with zeros when there is no --image
+  --input-type <TYPE>     zero input element type: f32, u8, i32 or i64 [default: f32]
+  --input <NAME>          input tensor name [default: input]
+  --output <NAME>         output tensor name, repeatable [default: output]
+  --output-type <TYPE>    output element type: f32, u8, i32 or i64 [default: f32]
+  --tag <TAG>             SavedModel tag, repeatable [default: serve]
+  --iterations <N>        recorded iterations [default: 100]
+  --warmup <N>            iterations run before recording [default: 10]
+  --json                  print the report as JSON";
+
+fn run(args: &Args) -> Result<()> {
+    let input_name = args.get("input").unwrap_or("input");
+    let iterations: usize = args.parsed("iterations", 100)?;
+    let warmup: usize = args.parsed("warmup", 10)?;
+    let output_type = args.element_type("output-type")?;
+
+    // The image is read once and decoded in every iteration; without an image
+    // the same zero tensor is fed every time.
+    let image = match args.get("image") {
+        Some(path) => {
+            let (w, h) = args.image_size()?;
+            let buf = fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+            let decode = decoder(args, path.to_lowercase().ends_with(".png"))?;
+            Some((buf, w, h, decode))
+        }
+        None => None,
+    };
+    let (shape, zero_input) = match (&image, args.get("shape")) {
+        (_, Some(shape)) => {
+            let shape = parse_shape(shape)?;
+            let zero_input = match image {
+                Some(_) => None,
+                None => {
+                    let len = shape.iter().product::<u64>() as usize;
+                    Some(Tensor::zeros(args.element_type("input-type")?, len)?)
+                }
+            };
+            (shape, zero_input)
+        }
+        (Some((_, w, h, _)), None) => (vec![1, *h as u64, *w as u64, 3], None),
+        (None, None) => return Err("either --image or --shape is required".into()),
+    };
+
+    let start = Instant::now();
+    let mut session = open_session(args)?;
+    let load = start.elapsed();
+    let outputs = args.all("output", "output");
+    for name in &outputs {
+        session.add_output(name);
+    }
+
+    // The first failed read ends the benchmark after its iteration.
+    let mut read_error = None;
+    let mut profiler = Profiler::new(warmup);
+    for _ in 0..warmup + iterations {
+        profiler.iteration(|p| {
+            let decoded = image
+                .as_ref()
+                .map(|(buf, w, h, decode)| p.time("decode", || decode(buf, *w, *h)));
+            let input = decoded.as_ref().or(zero_input.as_ref()).unwrap();
+            // TensorFlow sessions append inputs, so the previous iteration's
+            // input is cleared first.
+            p.time("add_input", || {
+                input.add_to(session.clear_input(), input_name, &shape)
+            });
+            p.time("run", || {
+                session.run();
+            });
+            p.time("get_output", || {
+                for name in &outputs {
+                    if let Err(e) = Tensor::read(&session, name, output_type) {
+                        read_error.get_or_insert(e);
+                    }
+                }
+            });
+        });
+        if let Some(e) = read_error {
+            return Err(e.into());
+        }
+    }
+
+    let report = profiler.report();
+    if args.has("json") {
+        println!(
+            "{{\"model\": \"{}\", \"load_ms\": {:.3}, \"report\": {}}}",
+            args.model.replace('\\', "\\\\").replace('"', "\\\""),
+            load.as_secs_f64() * 1000.0,
+            report.to_json()
+        );
+    } else {
+        println!("model: {}", args.model);
+        println!("load: {:.3} ms", load.as_secs_f64() * 1000.0);
+        print!("{}", report);
+    }
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> ExitCode {
+    eprintln!("tfbench runs in WasmEdge: build it with `--target wasm32-wasi`");
+    ExitCode::FAILURE
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() -> ExitCode {
+    common::main(USAGE, &["json"], run)
+}
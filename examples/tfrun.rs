@@ -0,0 +1,176 @@
+// Run a TensorFlow or TensorFlow-Lite model once on an image or `.npy` input,
+// and print the top-k classes or dump the outputs to `.npy` files.
+//
+//     cargo build --release --target wasm32-wasi --example tfrun
+//     wasmedge --dir .:. target/wasm32-wasi/release/examples/tfrun.wasm \
+//         mobilenet_v2.tflite --data grace_hopper.jpg --output MobilenetV2/Predictions/Softmax \
+//         --labels labels.txt
+//
+// The host functions only exist in WasmEdge, so native builds only print a hint.
+#![cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+
+mod common;
+
+use common::{decoder, open_session, parse_shape, Args, Result, Tensor};
+use std::fs;
+use std::process::ExitCode;
+use wasmedge_tensorflow_interface::error;
+use wasmedge_tensorflow_interface::npy;
+use wasmedge_tensorflow_interface::postprocess::classification::{classify, Activation, Labels};
+use wasmedge_tensorflow_interface::record::{Backend, Recorder};
+use wasmedge_tensorflow_interface::DataType;
+
+const USAGE: &str = "usage: tfrun <MODEL> [OPTIONS]
+
+MODEL is a frozen graph (.pb), a SavedModel directory or a TensorFlow-Lite model (.tflite).
+
+options:
+  --data <FILE>           input data: a JPEG or PNG image, or a .npy array
+  --input <NAME>          input tensor name [default: input]
+  --shape <D0,D1,...>     input shape [default: the .npy shape, or 1,H,W,3 for images]
+  --image-size <WxH>      image resize [default: 224x224]
+  --image-type <TYPE>     image pixels: rgb32f, bgr32f, rgb8 or bgr8 [default: rgb32f]
+  --output <NAME>         output tensor name, repeatable [default: output]
+  --output-type <TYPE>    output element type: f32, u8, i32 or i64 [default: f32]
+  --tag <TAG>             SavedModel tag, repeatable [default: serve]
+  --top-k <K>             print the K best classes of each output [default: 5]
+  --labels <FILE>         label file for the top-k classes
+  --softmax               apply a softmax to the outputs before ranking
+  --save <PREFIX>         write each output to <PREFIX><output name>.npy
+  --repeat <N>            run the model N times and report the average run time
+  --record <FILE>         record the run for `record::Replay`";
+
+// Read a `.npy` array of one of the element types the runner handles.
+fn read_npy(path: &str) -> error::Result<(Tensor, Vec<u64>)> {
+    let array = npy::read(path)?;
+    let tensor = match array.data_type() {
+        DataType::Float => Tensor::F32(array.to_vec()?),
+        DataType::UInt8 => Tensor::U8(array.to_vec()?),
+        DataType::Int32 => Tensor::I32(array.to_vec()?),
+        DataType::Int64 => Tensor::I64(array.to_vec()?),
+        t => return Err(npy::Error::Unsupported(format!("element type {}", t)).into()),
+    };
+    Ok((tensor, array.shape().to_vec()))
+}
+
+// Write a tensor as a `.npy` array with the given shape.
+fn write_npy(path: &str, tensor: &Tensor, shape: &[u64]) -> error::Result<()> {
+    match tensor {
+        Tensor::F32(v) => npy::write(path, v, shape)?,
+        Tensor::U8(v) => npy::write(path, v, shape)?,
+        Tensor::I32(v) => npy::write(path, v, shape)?,
+        Tensor::I64(v) => npy::write(path, v, shape)?,
+    }
+    Ok(())
+}
+
+fn load_image(args: &Args, path: &str) -> Result<(Tensor, Vec<u64>)> {
+    let buf = fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+    let (w, h) = args.image_size()?;
+    let decode = decoder(args, path.to_lowercase().ends_with(".png"))?;
+    Ok((decode(&buf, w, h), vec![1, h as u64, w as u64, 3]))
+}
+
+fn run(args: &Args) -> Result<()> {
+    let (input, default_shape) = match args.get("data") {
+        Some(path) if path.ends_with(".npy") => {
+            read_npy(path).map_err(|e| format!("{}: {}", path, e))?
+        }
+        Some(path) => load_image(args, path)?,
+        None => return Err("missing --data".into()),
+    };
+    let shape = match args.get("shape") {
+        Some(shape) => parse_shape(shape)?,
+        None => default_shape,
+    };
+    let elements: u64 = shape.iter().product();
+    if elements as usize != input.len() {
+        return Err(format!(
+            "input shape {:?} holds {} elements, the data has {}",
+            shape,
+            elements,
+            input.len()
+        )
+        .into());
+    }
+
+    let mut session = open_session(args)?;
+    match args.get("record") {
+        Some(path) => {
+            let mut recorder = Recorder::create(session, path)?;
+            infer(args, &mut recorder, &input, &shape)?;
+            Ok(recorder.finish()?)
+        }
+        None => infer(args, &mut session, &input, &shape),
+    }
+}
+
+// Run the model on the input and report its outputs.
+fn infer<B: Backend>(args: &Args, session: &mut B, input: &Tensor, shape: &[u64]) -> Result<()> {
+    let input_name = args.get("input").unwrap_or("input");
+    input.add_to(session, input_name, shape);
+    let outputs = args.all("output", "output");
+    for name in &outputs {
+        session.add_output(name);
+    }
+
+    let repeat: u32 = args.parsed("repeat", 1)?;
+    let start = std::time::Instant::now();
+    for _ in 0..repeat.max(1) {
+        session.run();
+    }
+    if repeat > 1 {
+        println!(
+            "average run time: {:.3} ms",
+            start.elapsed().as_secs_f64() * 1000.0 / repeat as f64
+        );
+    }
+
+    let labels = match args.get("labels") {
+        Some(path) => Labels::from_file(path).map_err(|e| format!("{}: {}", path, e))?,
+        None => Labels::default(),
+    };
+    let activation = if args.has("softmax") {
+        Activation::Softmax
+    } else {
+        Activation::None
+    };
+    let k: usize = args.parsed("top-k", 5)?;
+    let output_type = args.element_type("output-type")?;
+    for name in &outputs {
+        let output = Tensor::read(session, name, output_type)?;
+        println!("{}: {} elements", name, output.len());
+        if let Some(prefix) = args.get("save") {
+            let path = format!("{}{}.npy", prefix, name.replace(['/', ':'], "_"));
+            // Without output shapes, save the output as a flat array.
+            let shape = session.get_output_shape(name).unwrap_or_else(|e| {
+                eprintln!("  {}: saving {} as a flat array", e, name);
+                vec![output.len() as u64]
+            });
+            write_npy(&path, &output, &shape).map_err(|e| format!("{}: {}", path, e))?;
+            println!("  saved to {}", path);
+        }
+        if k > 0 {
+            for p in classify(&output.to_f32(), k, activation, &labels) {
+                println!(
+                    "  {:>6} {:.6} {}",
+                    p.class_id,
+                    p.score,
+                    p.label.unwrap_or_default()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> ExitCode {
+    eprintln!("tfrun runs in WasmEdge: build it with `--target wasm32-wasi`");
+    ExitCode::FAILURE
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() -> ExitCode {
+    common::main(USAGE, &["softmax"], run)
+}
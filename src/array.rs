@@ -0,0 +1,102 @@
+// ndarray conversions for session inputs and outputs, enabled by the `ndarray` feature.
+
+use crate::error::{Error, Result};
+use crate::{TFLiteSession, TFSession, TensorType};
+use ndarray::{ArrayBase, ArrayD, Data, Dimension, IxDyn};
+
+// Borrow the array as a standard layout slice, copying non-contiguous views.
+fn with_standard_layout<T, S, D, R>(array: &ArrayBase<S, D>, f: impl FnOnce(&[T]) -> R) -> R
+where
+    T: TensorType,
+    S: Data<Elem = T>,
+    D: Dimension,
+{
+    match array.as_slice() {
+        Some(slice) => f(slice),
+        None => f(array.as_standard_layout().as_slice().unwrap()),
+    }
+}
+
+// Build an array from output data and the shape reported by the host, which
+// must hold the same number of elements.
+fn to_array<T: TensorType>(data: Vec<T>, shape: Vec<u64>) -> Result<ArrayD<T>> {
+    let shape = shape
+        .into_iter()
+        .map(usize::try_from)
+        .collect::<std::result::Result<Vec<usize>, _>>()
+        .map_err(|_| Error::TooLarge)?;
+    let expected = shape
+        .iter()
+        .try_fold(1usize, |n, d| n.checked_mul(*d))
+        .ok_or(Error::TooLarge)?;
+    if expected != data.len() {
+        return Err(Error::LengthMismatch {
+            expected,
+            actual: data.len(),
+        });
+    }
+    Ok(ArrayD::from_shape_vec(IxDyn(&shape), data).unwrap())
+}
+
+impl TFSession {
+    // Add an input tensor from an array, using the array shape as the tensor dimensions.
+    pub fn add_input_array<T, S, D>(
+        &mut self,
+        name: &str,
+        array: &ArrayBase<S, D>,
+    ) -> &mut TFSession
+    where
+        T: TensorType,
+        S: Data<Elem = T>,
+        D: Dimension,
+    {
+        let shape: Vec<u64> = array.shape().iter().map(|d| *d as u64).collect();
+        with_standard_layout(array, |data| self.add_input(name, data, &shape))
+    }
+
+    // Get output tensor data by name as an array with the tensor shape.
+    pub fn get_output_array<T: TensorType>(&self, name: &str) -> Result<ArrayD<T>> {
+        let shape = self.get_output_shape(name)?;
+        to_array(self.get_output(name), shape)
+    }
+}
+
+impl TFLiteSession {
+    // Add an input tensor from an array. The model defines the tensor dimensions.
+    pub fn add_input_array<T, S, D>(
+        &mut self,
+        name: &str,
+        array: &ArrayBase<S, D>,
+    ) -> &mut TFLiteSession
+    where
+        T: TensorType,
+        S: Data<Elem = T>,
+        D: Dimension,
+    {
+        with_standard_layout(array, |data| self.add_input(name, data))
+    }
+
+    // Get output tensor data by name as an array with the tensor shape.
+    pub fn get_output_array<T: TensorType>(&self, name: &str) -> Result<ArrayD<T>> {
+        let shape = self.get_output_shape(name)?;
+        to_array(self.get_output(name), shape)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_array_checks_the_shape() {
+        let array = to_array(vec![1f32, 2.0, 3.0, 4.0, 5.0, 6.0], vec![2, 3]).unwrap();
+        assert_eq!(array.shape(), [2, 3]);
+        assert!(matches!(
+            to_array(vec![1f32, 2.0, 3.0], vec![2, 3]),
+            Err(Error::LengthMismatch {
+                expected: 6,
+                actual: 3
+            })
+        ));
+    }
+}
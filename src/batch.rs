@@ -0,0 +1,149 @@
+//! Stacking of samples into a batched input tensor.
+//!
+//! ```rust, ignore
+//! let mut batch = BatchBuilder::new(&[224, 224, 3]).with_batch_size(4);
+//! for img in &images {
+//!     batch.push(&load_jpg_image_to_rgb32f(img, 224, 224)).unwrap();
+//! }
+//! let (data, shape) = batch.finish();
+//! session.add_input("input", data, &shape).add_output("MobilenetV2/Predictions/Softmax").run();
+//! let output: Vec<f32> = session.get_output("MobilenetV2/Predictions/Softmax");
+//! for scores in batch.split(&output).unwrap() {
+//!     // One slice per pushed image, the padding samples are skipped.
+//! }
+//! ```
+
+use crate::error::{Error, Result};
+use crate::TensorType;
+
+// Builds a `[n, ...sample_shape]` tensor from samples of identical shape.
+#[derive(Clone, Debug)]
+pub struct BatchBuilder<T> {
+    sample_shape: Vec<u64>,
+    sample_len: usize,
+    batch_size: Option<usize>,
+    samples: usize,
+    data: Vec<T>,
+}
+
+impl<T: TensorType> BatchBuilder<T> {
+    pub fn new(sample_shape: &[u64]) -> BatchBuilder<T> {
+        BatchBuilder {
+            sample_shape: sample_shape.to_vec(),
+            sample_len: sample_shape.iter().product::<u64>() as usize,
+            batch_size: None,
+            samples: 0,
+            data: Vec::new(),
+        }
+    }
+
+    // Use a fixed batch size, for models with a static batch dimension.
+    // A partial batch is padded with zero samples by `finish`.
+    pub fn with_batch_size(mut self, batch_size: usize) -> BatchBuilder<T> {
+        self.batch_size = Some(batch_size);
+        self.data.reserve(batch_size * self.sample_len);
+        self
+    }
+
+    // Append a sample to the batch.
+    pub fn push(&mut self, sample: &[T]) -> Result<&mut BatchBuilder<T>> {
+        if sample.len() != self.sample_len {
+            return Err(Error::LengthMismatch {
+                expected: self.sample_len,
+                actual: sample.len(),
+            });
+        }
+        if self.is_full() {
+            return Err(Error::BatchFull(self.batch_len()));
+        }
+        self.data.truncate(self.samples * self.sample_len);
+        self.data.extend_from_slice(sample);
+        self.samples += 1;
+        Ok(self)
+    }
+
+    // Number of pushed samples, not counting the padding.
+    pub fn len(&self) -> usize {
+        self.samples
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples == 0
+    }
+
+    // Whether a fixed-size batch cannot take more samples.
+    pub fn is_full(&self) -> bool {
+        self.batch_size.is_some_and(|n| self.samples >= n)
+    }
+
+    // Batch dimension of the tensor built by `finish`.
+    pub fn batch_len(&self) -> usize {
+        self.batch_size.unwrap_or(self.samples)
+    }
+
+    // Dimensions of the tensor built by `finish`.
+    pub fn shape(&self) -> Vec<u64> {
+        let mut shape = vec![self.batch_len() as u64];
+        shape.extend_from_slice(&self.sample_shape);
+        shape
+    }
+
+    // Pad a partial batch and return the tensor data and dimensions.
+    pub fn finish(&mut self) -> (&[T], Vec<u64>) {
+        self.data
+            .resize(self.batch_len() * self.sample_len, T::default());
+        (&self.data, self.shape())
+    }
+
+    // Split a batched output into one slice per pushed sample. The output length
+    // must be a non-zero multiple of the batch dimension.
+    pub fn split<'a, U>(&self, output: &'a [U]) -> Result<Vec<&'a [U]>> {
+        let batch_len = self.batch_len();
+        if batch_len == 0 {
+            return Ok(Vec::new());
+        }
+        if output.is_empty() || !output.len().is_multiple_of(batch_len) {
+            return Err(Error::BatchMismatch {
+                batch_len,
+                len: output.len(),
+            });
+        }
+        Ok(output
+            .chunks_exact(output.len() / batch_len)
+            .take(self.samples)
+            .collect())
+    }
+
+    // Remove the samples, keeping the allocation for the next batch.
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.samples = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_skips_padding() {
+        let mut batch = BatchBuilder::<f32>::new(&[2]).with_batch_size(3);
+        batch.push(&[1.0, 2.0]).unwrap().push(&[3.0, 4.0]).unwrap();
+        let (_, shape) = batch.finish();
+        assert_eq!(shape, [3, 2]);
+        let output = [10, 11, 20, 21, 0, 0];
+        assert_eq!(batch.split(&output).unwrap(), [&[10, 11], &[20, 21]]);
+    }
+
+    #[test]
+    fn split_rejects_uneven_outputs() {
+        let mut batch = BatchBuilder::<f32>::new(&[1]);
+        batch.push(&[1.0]).unwrap().push(&[2.0]).unwrap();
+        for output in [&[][..], &[1], &[1, 2, 3]] {
+            assert!(matches!(
+                batch.split(output),
+                Err(Error::BatchMismatch { batch_len: 2, len }) if len == output.len()
+            ));
+        }
+    }
+}
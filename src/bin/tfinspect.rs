@@ -0,0 +1,29 @@
+// Print the inputs, outputs, op counts and metadata of TensorFlow and
+// TensorFlow-Lite models:
+//
+//     cargo run --features tfinspect --bin tfinspect -- <model.pb | saved_model_dir | model.tflite>
+
+use std::process::ExitCode;
+use wasmedge_tensorflow_interface::inspect;
+
+fn main() -> ExitCode {
+    let paths: Vec<String> = std::env::args().skip(1).collect();
+    if paths.is_empty() || paths.iter().any(|p| p == "-h" || p == "--help") {
+        eprintln!("usage: tfinspect <model.pb | saved_model_dir | model.tflite>...");
+        return ExitCode::FAILURE;
+    }
+    let mut status = ExitCode::SUCCESS;
+    for path in &paths {
+        if paths.len() > 1 {
+            println!("== {}", path);
+        }
+        match inspect::inspect_path(path) {
+            Ok(info) => print!("{}", info),
+            Err(e) => {
+                eprintln!("{}: {}", path, e);
+                status = ExitCode::FAILURE;
+            }
+        }
+    }
+    status
+}
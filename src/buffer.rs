@@ -0,0 +1,75 @@
+//! Reusable output buffers.
+//!
+//! ```rust, ignore
+//! let mut scores = OutputBuffer::<f32>::new();
+//! loop {
+//!     session.clear_input().add_input("input", &next_frame(), &[1, 224, 224, 3]).run();
+//!     let res = session.get_output_buffered("MobilenetV2/Predictions/Softmax", &mut scores).unwrap();
+//! }
+//! ```
+
+use crate::TensorType;
+use std::ops::Deref;
+
+// An output tensor buffer that keeps its allocation across runs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OutputBuffer<T> {
+    data: Vec<T>,
+}
+
+impl<T: TensorType> OutputBuffer<T> {
+    pub fn new() -> OutputBuffer<T> {
+        OutputBuffer { data: Vec::new() }
+    }
+
+    // Create a buffer that holds `capacity` elements without reallocating.
+    pub fn with_capacity(capacity: usize) -> OutputBuffer<T> {
+        OutputBuffer {
+            data: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.data.capacity()
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    // Set the length, only reallocating when the capacity is exceeded.
+    pub(crate) fn resize(&mut self, len: usize) -> &mut [T] {
+        self.data.resize(len, T::default());
+        &mut self.data
+    }
+}
+
+impl<T> Deref for OutputBuffer<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resize_keeps_the_allocation() {
+        let mut buf = OutputBuffer::<f32>::with_capacity(8);
+        buf.resize(8).copy_from_slice(&[1.0; 8]);
+        let capacity = buf.capacity();
+        assert_eq!(buf.resize(3), [1.0; 3]);
+        assert_eq!(buf.len(), 3);
+        // Growing back within the capacity zero-fills the new elements.
+        assert_eq!(buf.resize(5), [1.0, 1.0, 1.0, 0.0, 0.0]);
+        assert_eq!(buf.capacity(), capacity);
+        assert_eq!(buf.into_vec().len(), 5);
+    }
+}
@@ -0,0 +1,131 @@
+//! Conversions between `f32` buffers and the half-precision and complex tensor
+//! element types, enabled by the `half` and `num-complex` features, and of real
+//! tensor elements to `f64`.
+//!
+//! ```rust, ignore
+//! let input = convert::f16_from_f32(&flat_img);
+//! session.add_input("input", &input, &[1, 224, 224, 3]);
+//! ```
+
+use crate::TensorType;
+#[cfg(feature = "half")]
+use half::{bf16, f16, slice::HalfFloatSliceExt};
+#[cfg(feature = "num-complex")]
+use num_complex::Complex32;
+
+// Convert `f32` values to `float16`.
+#[cfg(feature = "half")]
+pub fn f16_from_f32(values: &[f32]) -> Vec<f16> {
+    let mut out = vec![f16::ZERO; values.len()];
+    out.convert_from_f32_slice(values);
+    out
+}
+
+// Convert `float16` values to `f32`.
+#[cfg(feature = "half")]
+pub fn f32_from_f16(values: &[f16]) -> Vec<f32> {
+    values.to_f32_vec()
+}
+
+// Convert `f32` values to `bfloat16`.
+#[cfg(feature = "half")]
+pub fn bf16_from_f32(values: &[f32]) -> Vec<bf16> {
+    let mut out = vec![bf16::ZERO; values.len()];
+    out.convert_from_f32_slice(values);
+    out
+}
+
+// Convert `bfloat16` values to `f32`.
+#[cfg(feature = "half")]
+pub fn f32_from_bf16(values: &[bf16]) -> Vec<f32> {
+    values.to_f32_vec()
+}
+
+// Convert real `f32` values to `complex64` values with a zero imaginary part.
+#[cfg(feature = "num-complex")]
+pub fn complex64_from_f32(values: &[f32]) -> Vec<Complex32> {
+    values.iter().map(|re| Complex32::new(*re, 0.0)).collect()
+}
+
+// Split `complex64` values into their real and imaginary parts.
+#[cfg(feature = "num-complex")]
+pub fn f32_parts_from_complex64(values: &[Complex32]) -> (Vec<f32>, Vec<f32>) {
+    values.iter().map(|c| (c.re, c.im)).unzip()
+}
+
+// Magnitudes of `complex64` values, e.g. the spectrum of an FFT output.
+#[cfg(feature = "num-complex")]
+pub fn f32_norm_from_complex64(values: &[Complex32]) -> Vec<f32> {
+    values.iter().map(|c| c.norm()).collect()
+}
+
+// Real tensor element types, converted to `f64` to compare and rank them.
+// Integers beyond 2^53 in magnitude are rounded to the nearest `f64`.
+pub trait ToF64: TensorType {
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! to_f64 {
+    ($($rust_type:ty),*) => {
+        $(
+            impl ToF64 for $rust_type {
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+to_f64!(f32, f64, i8, i16, i32, i64, u8, u16, u32, u64);
+
+impl ToF64 for bool {
+    fn to_f64(self) -> f64 {
+        u8::from(self) as f64
+    }
+}
+
+#[cfg(feature = "half")]
+impl ToF64 for f16 {
+    fn to_f64(self) -> f64 {
+        f16::to_f64(self)
+    }
+}
+
+#[cfg(feature = "half")]
+impl ToF64 for bf16 {
+    fn to_f64(self) -> f64 {
+        bf16::to_f64(self)
+    }
+}
+
+#[cfg(all(test, any(feature = "half", feature = "num-complex")))]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "half")]
+    #[test]
+    fn half_conversions() {
+        let values = [1.0, -0.5, 65504.0, 1e-8];
+        let halves = f16_from_f32(&values);
+        assert_eq!(f32_from_f16(&halves), [1.0, -0.5, 65504.0, 0.0]);
+        // bfloat16 keeps the `f32` range with 8 bits of precision.
+        let halves = bf16_from_f32(&[1.0, 2f32.powi(100), 1.0 + 2f32.powi(-9)]);
+        assert_eq!(f32_from_bf16(&halves), [1.0, 2f32.powi(100), 1.0]);
+    }
+
+    #[cfg(feature = "num-complex")]
+    #[test]
+    fn complex_conversions() {
+        let values = complex64_from_f32(&[3.0, -1.0]);
+        assert_eq!(
+            values,
+            [Complex32::new(3.0, 0.0), Complex32::new(-1.0, 0.0)]
+        );
+        let values = [Complex32::new(3.0, 4.0), Complex32::new(0.0, -2.0)];
+        assert_eq!(
+            f32_parts_from_complex64(&values),
+            (vec![3.0, 0.0], vec![4.0, -2.0])
+        );
+        assert_eq!(f32_norm_from_complex64(&values), [5.0, 2.0]);
+    }
+}
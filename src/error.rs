@@ -0,0 +1,241 @@
+//! Error type of the fallible session functions.
+
+use crate::{
+    npy, DataType, WasmedgeImageErrno, WasmedgeTfErrno, WasmedgeTfliteErrno,
+    WASMEDGE_IMAGE_ERRNO_FAIL, WASMEDGE_TFLITE_ERRNO_RUNTIME_ERROR,
+    WASMEDGE_TF_ERRNO_RUNTIME_ERROR,
+};
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum Error {
+    // Error returned by a `wasmedge_tensorflow` host function.
+    TensorFlow(WasmedgeTfErrno),
+    // Error returned by a `wasmedge_tensorflowlite` host function.
+    TensorFlowLite(WasmedgeTfliteErrno),
+    // Error returned by a `wasmedge_image` host function.
+    Image(WasmedgeImageErrno),
+    // Error returned by a `wasi_ephemeral_nn` host function.
+    #[cfg(feature = "wasi-nn")]
+    WasiNn(crate::NnErrno),
+    // The caller-provided buffer cannot hold the tensor.
+    BufferTooSmall {
+        required: usize,
+        actual: usize,
+    },
+    // A buffer does not have the expected element count.
+    LengthMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    // A fixed-size batch already holds its batch size of samples.
+    BatchFull(usize),
+    // A batched output cannot be split evenly into the samples of the batch.
+    BatchMismatch {
+        batch_len: usize,
+        len: usize,
+    },
+    // An input does not have the element type of its `InputSpec`: `expected` is
+    // the type of the spec and `actual` the one of the caller.
+    TypeMismatch {
+        expected: DataType,
+        actual: DataType,
+    },
+    // An input does not have the rank of its `InputSpec`.
+    RankMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    // An input dimension differs from the static one of its `InputSpec`.
+    DimMismatch {
+        index: usize,
+        expected: u64,
+        actual: u64,
+    },
+    // A tensor exceeds the `i32` lengths of the host functions.
+    TooLarge,
+    // The operation is not available with the enabled features or backend.
+    Unsupported(&'static str),
+    // A tensor name is not mapped to a WASI-NN index.
+    #[cfg(feature = "wasi-nn")]
+    UnmappedTensor,
+    // Error reading or writing a file, such as a `record` trace.
+    Io(io::Error),
+    // Error reading or writing a `.npy` or `.npz` file.
+    Npy(npy::Error),
+    // Error reading a model with the `inspect` module.
+    #[cfg(any(feature = "tfinspect", feature = "wasi-nn"))]
+    Inspect(crate::inspect::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// Write a host errno by name when the bindings know its code, up to `last`, or
+// as `errno(N)` for the codes a newer plug-in may return.
+fn write_errno(
+    f: &mut fmt::Formatter<'_>,
+    module: &str,
+    raw: u32,
+    last: u32,
+    name: impl FnOnce() -> &'static str,
+) -> fmt::Result {
+    if raw <= last {
+        write!(f, "{}: {} (error {})", module, name(), raw)
+    } else {
+        write!(f, "{}: errno({})", module, raw)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::TensorFlow(e) => write_errno(
+                f,
+                "wasmedge_tensorflow",
+                e.raw(),
+                WASMEDGE_TF_ERRNO_RUNTIME_ERROR.raw(),
+                || e.name(),
+            ),
+            Error::TensorFlowLite(e) => write_errno(
+                f,
+                "wasmedge_tensorflowlite",
+                e.raw(),
+                WASMEDGE_TFLITE_ERRNO_RUNTIME_ERROR.raw(),
+                || e.name(),
+            ),
+            Error::Image(e) => write_errno(
+                f,
+                "wasmedge_image",
+                e.raw(),
+                WASMEDGE_IMAGE_ERRNO_FAIL.raw(),
+                || e.name(),
+            ),
+            #[cfg(feature = "wasi-nn")]
+            Error::WasiNn(e) => write_errno(
+                f,
+                "wasi_ephemeral_nn",
+                e.raw(),
+                crate::generated_nn::NN_ERRNO_NOT_FOUND.raw(),
+                || e.name(),
+            ),
+            Error::BufferTooSmall { required, actual } => write!(
+                f,
+                "buffer too small: {} elements required, {} provided",
+                required, actual
+            ),
+            Error::LengthMismatch { expected, actual } => write!(
+                f,
+                "length mismatch: {} elements expected, {} provided",
+                expected, actual
+            ),
+            Error::BatchFull(n) => write!(f, "batch full: {} samples", n),
+            Error::BatchMismatch { batch_len, len } => write!(
+                f,
+                "batch mismatch: {} elements cannot be split into {} samples",
+                len, batch_len
+            ),
+            Error::TypeMismatch { expected, actual } => write!(
+                f,
+                "type mismatch: {} expected, {} provided",
+                expected, actual
+            ),
+            Error::RankMismatch { expected, actual } => write!(
+                f,
+                "rank mismatch: {} dimensions expected, {} provided",
+                expected, actual
+            ),
+            Error::DimMismatch {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "dimension mismatch: dimension {} is {}, {} provided",
+                index, expected, actual
+            ),
+            Error::TooLarge => write!(f, "tensor too large for the host functions"),
+            Error::Unsupported(what) => write!(f, "unsupported: {}", what),
+            #[cfg(feature = "wasi-nn")]
+            Error::UnmappedTensor => write!(
+                f,
+                "tensor name not mapped to an index, see `set_input_names` and `set_output_names`"
+            ),
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Npy(e) => write!(f, "{}", e),
+            #[cfg(any(feature = "tfinspect", feature = "wasi-nn"))]
+            Error::Inspect(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Npy(e) => Some(e),
+            #[cfg(any(feature = "tfinspect", feature = "wasi-nn"))]
+            Error::Inspect(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<WasmedgeTfErrno> for Error {
+    fn from(e: WasmedgeTfErrno) -> Error {
+        Error::TensorFlow(e)
+    }
+}
+
+impl From<WasmedgeTfliteErrno> for Error {
+    fn from(e: WasmedgeTfliteErrno) -> Error {
+        Error::TensorFlowLite(e)
+    }
+}
+
+impl From<WasmedgeImageErrno> for Error {
+    fn from(e: WasmedgeImageErrno) -> Error {
+        Error::Image(e)
+    }
+}
+
+#[cfg(feature = "wasi-nn")]
+impl From<crate::NnErrno> for Error {
+    fn from(e: crate::NnErrno) -> Error {
+        Error::WasiNn(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<npy::Error> for Error {
+    fn from(e: npy::Error) -> Error {
+        Error::Npy(e)
+    }
+}
+
+#[cfg(any(feature = "tfinspect", feature = "wasi-nn"))]
+impl From<crate::inspect::Error> for Error {
+    fn from(e: crate::inspect::Error) -> Error {
+        Error::Inspect(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn file_errors_keep_their_source() {
+        let e = Error::from(npy::Error::Malformed("truncated .npy header"));
+        assert_eq!(e.to_string(), "malformed file: truncated .npy header");
+        assert!(e.source().is_some());
+        let e = Error::from(io::Error::new(io::ErrorKind::NotFound, "missing"));
+        assert!(matches!(&e, Error::Io(e) if e.kind() == io::ErrorKind::NotFound));
+        assert!(Error::TooLarge.source().is_none());
+    }
+}
@@ -0,0 +1,331 @@
+//! Non-blocking session runs for async runtimes.
+//!
+//! `run_async` starts the run on the host and polls it for completion, yielding
+//! to the executor in between so that other tasks make progress while the
+//! plug-in computes. Background runs use the `start_session`, `poll_session`
+//! and `wait_session` host functions, which the released plug-ins do not
+//! provide, so they need the `extended-abi` feature. Without it, or when the
+//! plug-in does not report the `ASYNC` capability or refuses to start a
+//! background run, the session is run blocking on the first poll instead.
+//!
+//! The host does not notify the guest when a run completes, so by default the
+//! future wakes itself up after every poll: the executor polls the host in a
+//! loop, and keeps a core busy for the whole run when no other task is ready.
+//! With `with_timer`, the future instead waits on a timer of the runtime
+//! between polls, backing off from 1 ms to 50 ms, which bounds the polling
+//! cost at the price of noticing the completion up to a delay later.
+//!
+//! ```rust, ignore
+//! session.add_input("input", &flat_img, &[1, 224, 224, 3]).add_output("MobilenetV2/Predictions/Softmax");
+//! session.run_async().with_timer(tokio::time::sleep).await.unwrap();
+//! let res_vec: Vec<f32> = session.get_output("MobilenetV2/Predictions/Softmax");
+//! ```
+
+use crate::error::Result;
+#[cfg(feature = "extended-abi")]
+use crate::host::{self, Capabilities};
+use crate::{generated_tf, TFSession};
+#[cfg(not(feature = "wasi-nn"))]
+use crate::{generated_tflite, TFLiteSession};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+// Delays between host polls with a timer: the first one, doubled after every
+// poll up to the last one.
+const MIN_POLL_DELAY: Duration = Duration::from_millis(1);
+const MAX_POLL_DELAY: Duration = Duration::from_millis(50);
+
+// Sessions that can run in the background on the host. Sessions without
+// background runs only implement `run_blocking`.
+pub trait AsyncRun {
+    // Start a background run, returning false when the host does not support it.
+    fn start(&mut self) -> bool {
+        false
+    }
+
+    // Whether the background run has completed, or the error it failed with.
+    fn poll_done(&mut self) -> Result<bool> {
+        Ok(true)
+    }
+
+    // Block until the background run has stopped.
+    fn wait(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    // Run the session, blocking until it completes.
+    fn run_blocking(&mut self) -> Result<()>;
+}
+
+// Whether a run in the `state` reported by the TensorFlow plug-in has completed.
+// A failed run reports the runtime error of a failed blocking run.
+#[cfg(feature = "extended-abi")]
+fn tf_done(state: generated_tf::SessionState) -> Result<bool> {
+    match state {
+        generated_tf::SESSION_STATE_RUNNING => Ok(false),
+        generated_tf::SESSION_STATE_DONE => Ok(true),
+        _ => Err(generated_tf::WASMEDGE_TF_ERRNO_RUNTIME_ERROR.into()),
+    }
+}
+
+impl AsyncRun for TFSession {
+    #[cfg(feature = "extended-abi")]
+    fn start(&mut self) -> bool {
+        host::tensorflow_supports(Capabilities::ASYNC)
+            && unsafe { generated_tf::start_session(self.context).is_ok() }
+    }
+
+    #[cfg(feature = "extended-abi")]
+    fn poll_done(&mut self) -> Result<bool> {
+        tf_done(unsafe { generated_tf::poll_session(self.context)? })
+    }
+
+    #[cfg(feature = "extended-abi")]
+    fn wait(&mut self) -> Result<()> {
+        tf_done(unsafe { generated_tf::wait_session(self.context)? }).map(|_| ())
+    }
+
+    fn run_blocking(&mut self) -> Result<()> {
+        unsafe {
+            generated_tf::run_session(self.context)?;
+        }
+        Ok(())
+    }
+}
+
+// Whether a run in the `state` reported by the TensorFlow-Lite plug-in has
+// completed. A failed run reports the runtime error of a failed blocking run.
+#[cfg(all(feature = "extended-abi", not(feature = "wasi-nn")))]
+fn tflite_done(state: generated_tflite::SessionState) -> Result<bool> {
+    match state {
+        generated_tflite::SESSION_STATE_RUNNING => Ok(false),
+        generated_tflite::SESSION_STATE_DONE => Ok(true),
+        _ => Err(generated_tflite::WASMEDGE_TFLITE_ERRNO_RUNTIME_ERROR.into()),
+    }
+}
+
+#[cfg(not(feature = "wasi-nn"))]
+impl AsyncRun for TFLiteSession {
+    #[cfg(feature = "extended-abi")]
+    fn start(&mut self) -> bool {
+        host::tensorflow_lite_supports(Capabilities::ASYNC)
+            && unsafe { generated_tflite::start_session(self.context).is_ok() }
+    }
+
+    #[cfg(feature = "extended-abi")]
+    fn poll_done(&mut self) -> Result<bool> {
+        tflite_done(unsafe { generated_tflite::poll_session(self.context)? })
+    }
+
+    #[cfg(feature = "extended-abi")]
+    fn wait(&mut self) -> Result<()> {
+        tflite_done(unsafe { generated_tflite::wait_session(self.context)? }).map(|_| ())
+    }
+
+    fn run_blocking(&mut self) -> Result<()> {
+        unsafe {
+            generated_tflite::run_session(self.context)?;
+        }
+        Ok(())
+    }
+}
+
+type Timer<'a> = Pin<Box<dyn Future<Output = ()> + 'a>>;
+
+// Future of a session run, returned by `run_async`.
+// Dropping it before completion blocks until the host has stopped the run.
+pub struct RunFuture<'a, S: AsyncRun> {
+    session: &'a mut S,
+    started: bool,
+    done: bool,
+    // Creates the timer awaited between host polls.
+    sleep: Option<Box<dyn FnMut(Duration) -> Timer<'a> + 'a>>,
+    timer: Option<Timer<'a>>,
+    delay: Duration,
+}
+
+impl<'a, S: AsyncRun> RunFuture<'a, S> {
+    pub(crate) fn new(session: &'a mut S) -> RunFuture<'a, S> {
+        RunFuture {
+            session,
+            started: false,
+            done: false,
+            sleep: None,
+            timer: None,
+            delay: MIN_POLL_DELAY,
+        }
+    }
+
+    // Wait between host polls on the timers returned by `sleep`, such as
+    // `tokio::time::sleep`, instead of polling the host again right away.
+    pub fn with_timer<F>(mut self, mut sleep: impl FnMut(Duration) -> F + 'a) -> RunFuture<'a, S>
+    where
+        F: Future<Output = ()> + 'a,
+    {
+        self.sleep = Some(Box::new(move |delay| Box::pin(sleep(delay))));
+        self
+    }
+}
+
+impl<S: AsyncRun> Future for RunFuture<'_, S> {
+    type Output = Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.get_mut();
+        if !this.started {
+            this.started = true;
+            if !this.session.start() {
+                this.done = true;
+                return Poll::Ready(this.session.run_blocking());
+            }
+        }
+        if let Some(timer) = &mut this.timer {
+            if timer.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+            this.timer = None;
+        }
+        match this.session.poll_done() {
+            Ok(false) => {
+                let timer = this.sleep.as_mut().map(|sleep| sleep(this.delay));
+                match timer {
+                    Some(mut timer) => {
+                        this.delay = (this.delay * 2).min(MAX_POLL_DELAY);
+                        // Polling the timer registers the waker to wake up when it expires.
+                        if timer.as_mut().poll(cx).is_ready() {
+                            cx.waker().wake_by_ref();
+                        } else {
+                            this.timer = Some(timer);
+                        }
+                    }
+                    None => cx.waker().wake_by_ref(),
+                }
+                Poll::Pending
+            }
+            result => {
+                this.done = true;
+                Poll::Ready(result.map(|_| ()))
+            }
+        }
+    }
+}
+
+impl<S: AsyncRun> Drop for RunFuture<'_, S> {
+    fn drop(&mut self) {
+        if self.started && !self.done {
+            let _ = self.session.wait();
+        }
+    }
+}
+
+impl TFSession {
+    // Run session without blocking the executor.
+    pub fn run_async(&mut self) -> RunFuture<'_, TFSession> {
+        RunFuture::new(self)
+    }
+}
+
+#[cfg(not(feature = "wasi-nn"))]
+impl TFLiteSession {
+    // Run session without blocking the executor.
+    pub fn run_async(&mut self) -> RunFuture<'_, TFLiteSession> {
+        RunFuture::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use std::cell::RefCell;
+    use std::task::Waker;
+
+    // A background run reporting the given poll results in turn.
+    struct Mock {
+        polls: Vec<Result<bool>>,
+        waited: bool,
+    }
+
+    impl AsyncRun for Mock {
+        fn start(&mut self) -> bool {
+            true
+        }
+
+        fn poll_done(&mut self) -> Result<bool> {
+            self.polls.remove(0)
+        }
+
+        fn wait(&mut self) -> Result<()> {
+            self.waited = true;
+            Ok(())
+        }
+
+        fn run_blocking(&mut self) -> Result<()> {
+            unreachable!()
+        }
+    }
+
+    fn poll(future: &mut RunFuture<'_, Mock>) -> Poll<Result<()>> {
+        Pin::new(future).poll(&mut Context::from_waker(Waker::noop()))
+    }
+
+    #[test]
+    fn failed_run_resolves_to_error() {
+        let mut mock = Mock {
+            polls: vec![Ok(false), Err(Error::Unsupported("test"))],
+            waited: false,
+        };
+        let mut future = RunFuture::new(&mut mock);
+        assert!(poll(&mut future).is_pending());
+        assert!(matches!(
+            poll(&mut future),
+            Poll::Ready(Err(Error::Unsupported("test")))
+        ));
+        drop(future);
+        assert!(!mock.waited);
+    }
+
+    #[test]
+    fn dropping_a_running_future_waits() {
+        let mut mock = Mock {
+            polls: vec![Ok(false)],
+            waited: false,
+        };
+        let mut future = RunFuture::new(&mut mock);
+        assert!(poll(&mut future).is_pending());
+        drop(future);
+        assert!(mock.waited);
+    }
+
+    #[test]
+    fn timer_backs_off_between_polls() {
+        let mut mock = Mock {
+            polls: (0..8).map(|_| Ok(false)).chain([Ok(true)]).collect(),
+            waited: false,
+        };
+        let delays = RefCell::new(Vec::new());
+        let mut future = RunFuture::new(&mut mock).with_timer(|delay| {
+            delays.borrow_mut().push(delay.as_millis());
+            std::future::ready(())
+        });
+        while poll(&mut future).is_pending() {}
+        drop(future);
+        assert_eq!(*delays.borrow(), [1, 2, 4, 8, 16, 32, 50, 50]);
+    }
+
+    #[test]
+    fn host_is_not_polled_before_the_timer_expires() {
+        let mut mock = Mock {
+            polls: vec![Ok(false)],
+            waited: false,
+        };
+        let mut future = RunFuture::new(&mut mock).with_timer(|_| std::future::pending());
+        assert!(poll(&mut future).is_pending());
+        // Polling the host again would fail with no poll result left.
+        assert!(poll(&mut future).is_pending());
+        drop(future);
+        assert!(mock.waited);
+    }
+}
@@ -3,6 +3,7 @@
 // To regenerate this file run the `crates/witx-bindgen` command
 
 use core::fmt;
+use core::mem::MaybeUninit;
 #[repr(transparent)]
 #[derive(Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
 pub struct WasmedgeImageErrno(u32);
@@ -17,14 +18,14 @@ impl WasmedgeImageErrno {
         match self.0 {
             0 => "SUCCESS",
             1 => "FAIL",
-            _ => unsafe { core::hint::unreachable_unchecked() },
+            _ => "UNKNOWN",
         }
     }
     pub fn message(&self) -> &'static str {
         match self.0 {
             0 => "",
             1 => "",
-            _ => unsafe { core::hint::unreachable_unchecked() },
+            _ => "",
         }
     }
 }
@@ -48,6 +49,8 @@ extern crate std;
 #[cfg(feature = "std")]
 impl std::error::Error for WasmedgeImageErrno {}
 
+pub type AbiVersion = u32;
+pub type CapabilityFlags = u32;
 #[repr(transparent)]
 #[derive(Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
 pub struct WasmedgeImageRawType(u32);
@@ -66,7 +69,7 @@ impl WasmedgeImageRawType {
             1 => "BGR8",
             2 => "RGB32F",
             3 => "BGR32F",
-            _ => unsafe { core::hint::unreachable_unchecked() },
+            _ => "UNKNOWN",
         }
     }
     pub fn message(&self) -> &'static str {
@@ -75,7 +78,7 @@ impl WasmedgeImageRawType {
             1 => "",
             2 => "",
             3 => "",
-            _ => unsafe { core::hint::unreachable_unchecked() },
+            _ => "",
         }
     }
 }
@@ -91,6 +94,26 @@ impl fmt::Debug for WasmedgeImageRawType {
 
 pub type ImgBuffer<'a> = &'a [u8];
 pub type BufMaxSize = u32;
+pub unsafe fn version() -> Result<AbiVersion, WasmedgeImageErrno> {
+    let mut rp0 = MaybeUninit::<AbiVersion>::uninit();
+    let ret = wasmedge_image::version(rp0.as_mut_ptr() as i32);
+    match ret {
+        0 => Ok(core::ptr::read(rp0.as_mut_ptr() as i32 as *const AbiVersion)),
+        _ => Err(WasmedgeImageErrno(ret as u32)),
+    }
+}
+
+pub unsafe fn capabilities() -> Result<CapabilityFlags, WasmedgeImageErrno> {
+    let mut rp0 = MaybeUninit::<CapabilityFlags>::uninit();
+    let ret = wasmedge_image::capabilities(rp0.as_mut_ptr() as i32);
+    match ret {
+        0 => Ok(core::ptr::read(
+            rp0.as_mut_ptr() as i32 as *const CapabilityFlags
+        )),
+        _ => Err(WasmedgeImageErrno(ret as u32)),
+    }
+}
+
 pub unsafe fn load_jpg(
     input_img_buffer: ImgBuffer<'_>,
     out_width: u32,
@@ -140,6 +163,8 @@ pub unsafe fn load_png(
 pub mod wasmedge_image {
     #[link(wasm_import_module = "wasmedge_image")]
     extern "C" {
+        pub fn version(arg0: i32) -> i32;
+        pub fn capabilities(arg0: i32) -> i32;
         pub fn load_jpg(
             arg0: i32,
             arg1: i32,
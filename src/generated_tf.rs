@@ -26,7 +26,7 @@ impl WasmedgeTfErrno {
             3 => "MISSING_MEMORY",
             4 => "BUSY",
             5 => "RUNTIME_ERROR",
-            _ => unsafe { core::hint::unreachable_unchecked() },
+            _ => "UNKNOWN",
         }
     }
     pub fn message(&self) -> &'static str {
@@ -37,7 +37,7 @@ impl WasmedgeTfErrno {
             3 => "",
             4 => "",
             5 => "",
-            _ => unsafe { core::hint::unreachable_unchecked() },
+            _ => "",
         }
     }
 }
@@ -61,15 +61,76 @@ extern crate std;
 #[cfg(feature = "std")]
 impl std::error::Error for WasmedgeTfErrno {}
 
+pub type AbiVersion = u32;
+pub type CapabilityFlags = u32;
 pub type ModelBuffer<'a> = &'a [u8];
 pub type MetagraphTag<'a> = &'a str;
 pub type MetagraphTagList<'a> = &'a [MetagraphTag<'a>];
 pub type Session = u32;
+#[repr(transparent)]
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct SessionState(u32);
+pub const SESSION_STATE_RUNNING: SessionState = SessionState(0);
+pub const SESSION_STATE_DONE: SessionState = SessionState(1);
+pub const SESSION_STATE_FAILED: SessionState = SessionState(2);
+impl SessionState {
+    pub const fn raw(&self) -> u32 {
+        self.0
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self.0 {
+            0 => "RUNNING",
+            1 => "DONE",
+            2 => "FAILED",
+            _ => "UNKNOWN",
+        }
+    }
+    pub fn message(&self) -> &'static str {
+        match self.0 {
+            0 => "",
+            1 => "",
+            2 => "",
+            _ => "",
+        }
+    }
+}
+impl fmt::Debug for SessionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionState")
+            .field("code", &self.0)
+            .field("name", &self.name())
+            .field("message", &self.message())
+            .finish()
+    }
+}
+
 pub type Tensor = u32;
 pub type TensorSize = u32;
 pub type TensorType = u32;
 pub type TensorData<'a> = &'a [u8];
 pub type TensorDimensions<'a> = &'a [u64];
+pub type StringTensorData<'a> = &'a [u8];
+pub unsafe fn version() -> Result<AbiVersion, WasmedgeTfErrno> {
+    let mut rp0 = MaybeUninit::<AbiVersion>::uninit();
+    let ret = wasmedge_tensorflow::version(rp0.as_mut_ptr() as i32);
+    match ret {
+        0 => Ok(core::ptr::read(rp0.as_mut_ptr() as i32 as *const AbiVersion)),
+        _ => Err(WasmedgeTfErrno(ret as u32)),
+    }
+}
+
+pub unsafe fn capabilities() -> Result<CapabilityFlags, WasmedgeTfErrno> {
+    let mut rp0 = MaybeUninit::<CapabilityFlags>::uninit();
+    let ret = wasmedge_tensorflow::capabilities(rp0.as_mut_ptr() as i32);
+    match ret {
+        0 => Ok(core::ptr::read(
+            rp0.as_mut_ptr() as i32 as *const CapabilityFlags
+        )),
+        _ => Err(WasmedgeTfErrno(ret as u32)),
+    }
+}
+
 pub unsafe fn create_session(model_buffer: ModelBuffer<'_>) -> Result<Session, WasmedgeTfErrno> {
     let mut rp0 = MaybeUninit::<Session>::uninit();
     let ret = wasmedge_tensorflow::create_session(
@@ -101,6 +162,19 @@ pub unsafe fn create_session_saved_model(
     }
 }
 
+pub unsafe fn create_session_from_file(model_path: &str) -> Result<Session, WasmedgeTfErrno> {
+    let mut rp0 = MaybeUninit::<Session>::uninit();
+    let ret = wasmedge_tensorflow::create_session_from_file(
+        model_path.as_ptr() as i32,
+        model_path.len() as i32,
+        rp0.as_mut_ptr() as i32,
+    );
+    match ret {
+        0 => Ok(core::ptr::read(rp0.as_mut_ptr() as i32 as *const Session)),
+        _ => Err(WasmedgeTfErrno(ret as u32)),
+    }
+}
+
 pub unsafe fn delete_session(session: Session) -> Result<(), WasmedgeTfErrno> {
     let ret = wasmedge_tensorflow::delete_session(session as i32);
     match ret {
@@ -117,6 +191,36 @@ pub unsafe fn run_session(session: Session) -> Result<(), WasmedgeTfErrno> {
     }
 }
 
+pub unsafe fn start_session(session: Session) -> Result<(), WasmedgeTfErrno> {
+    let ret = wasmedge_tensorflow::start_session(session as i32);
+    match ret {
+        0 => Ok(()),
+        _ => Err(WasmedgeTfErrno(ret as u32)),
+    }
+}
+
+pub unsafe fn poll_session(session: Session) -> Result<SessionState, WasmedgeTfErrno> {
+    let mut rp0 = MaybeUninit::<SessionState>::uninit();
+    let ret = wasmedge_tensorflow::poll_session(session as i32, rp0.as_mut_ptr() as i32);
+    match ret {
+        0 => Ok(core::ptr::read(
+            rp0.as_mut_ptr() as i32 as *const SessionState
+        )),
+        _ => Err(WasmedgeTfErrno(ret as u32)),
+    }
+}
+
+pub unsafe fn wait_session(session: Session) -> Result<SessionState, WasmedgeTfErrno> {
+    let mut rp0 = MaybeUninit::<SessionState>::uninit();
+    let ret = wasmedge_tensorflow::wait_session(session as i32, rp0.as_mut_ptr() as i32);
+    match ret {
+        0 => Ok(core::ptr::read(
+            rp0.as_mut_ptr() as i32 as *const SessionState
+        )),
+        _ => Err(WasmedgeTfErrno(ret as u32)),
+    }
+}
+
 pub unsafe fn get_output_tensor(session: Session, name: &str) -> Result<Tensor, WasmedgeTfErrno> {
     let mut rp0 = MaybeUninit::<Tensor>::uninit();
     let ret = wasmedge_tensorflow::get_output_tensor(
@@ -164,6 +268,78 @@ pub unsafe fn get_tensor_data(
     }
 }
 
+pub unsafe fn get_string_tensor_len(
+    session: Session,
+    tensor: Tensor,
+) -> Result<TensorSize, WasmedgeTfErrno> {
+    let mut rp0 = MaybeUninit::<TensorSize>::uninit();
+    let ret = wasmedge_tensorflow::get_string_tensor_len(
+        session as i32,
+        tensor as i32,
+        rp0.as_mut_ptr() as i32,
+    );
+    match ret {
+        0 => Ok(core::ptr::read(rp0.as_mut_ptr() as i32 as *const TensorSize)),
+        _ => Err(WasmedgeTfErrno(ret as u32)),
+    }
+}
+
+pub unsafe fn get_string_tensor_data(
+    session: Session,
+    tensor: Tensor,
+    tensor_buf: *mut u8,
+    tensor_buf_max_size: TensorSize,
+) -> Result<TensorSize, WasmedgeTfErrno> {
+    let mut rp0 = MaybeUninit::<TensorSize>::uninit();
+    let ret = wasmedge_tensorflow::get_string_tensor_data(
+        session as i32,
+        tensor as i32,
+        tensor_buf as i32,
+        tensor_buf_max_size as i32,
+        rp0.as_mut_ptr() as i32,
+    );
+    match ret {
+        0 => Ok(core::ptr::read(rp0.as_mut_ptr() as i32 as *const TensorSize)),
+        _ => Err(WasmedgeTfErrno(ret as u32)),
+    }
+}
+
+pub unsafe fn get_tensor_dims_len(
+    session: Session,
+    tensor: Tensor,
+) -> Result<TensorSize, WasmedgeTfErrno> {
+    let mut rp0 = MaybeUninit::<TensorSize>::uninit();
+    let ret = wasmedge_tensorflow::get_tensor_dims_len(
+        session as i32,
+        tensor as i32,
+        rp0.as_mut_ptr() as i32,
+    );
+    match ret {
+        0 => Ok(core::ptr::read(rp0.as_mut_ptr() as i32 as *const TensorSize)),
+        _ => Err(WasmedgeTfErrno(ret as u32)),
+    }
+}
+
+pub unsafe fn get_tensor_dims(
+    session: Session,
+    tensor: Tensor,
+    dims_buf: *mut u64,
+    dims_buf_max_len: TensorSize,
+) -> Result<TensorSize, WasmedgeTfErrno> {
+    let mut rp0 = MaybeUninit::<TensorSize>::uninit();
+    let ret = wasmedge_tensorflow::get_tensor_dims(
+        session as i32,
+        tensor as i32,
+        dims_buf as i32,
+        dims_buf_max_len as i32,
+        rp0.as_mut_ptr() as i32,
+    );
+    match ret {
+        0 => Ok(core::ptr::read(rp0.as_mut_ptr() as i32 as *const TensorSize)),
+        _ => Err(WasmedgeTfErrno(ret as u32)),
+    }
+}
+
 pub unsafe fn append_input(
     session: Session,
     name: &str,
@@ -187,6 +363,27 @@ pub unsafe fn append_input(
     }
 }
 
+pub unsafe fn append_string_input(
+    session: Session,
+    name: &str,
+    dimension: TensorDimensions<'_>,
+    tensor_buf: StringTensorData<'_>,
+) -> Result<(), WasmedgeTfErrno> {
+    let ret = wasmedge_tensorflow::append_string_input(
+        session as i32,
+        name.as_ptr() as i32,
+        name.len() as i32,
+        dimension.as_ptr() as i32,
+        dimension.len() as i32,
+        tensor_buf.as_ptr() as i32,
+        tensor_buf.len() as i32,
+    );
+    match ret {
+        0 => Ok(()),
+        _ => Err(WasmedgeTfErrno(ret as u32)),
+    }
+}
+
 pub unsafe fn append_output(session: Session, name: &str) -> Result<(), WasmedgeTfErrno> {
     let ret =
         wasmedge_tensorflow::append_output(session as i32, name.as_ptr() as i32, name.len() as i32);
@@ -215,6 +412,8 @@ pub unsafe fn clear_output(session: Session) -> Result<(), WasmedgeTfErrno> {
 pub mod wasmedge_tensorflow {
     #[link(wasm_import_module = "wasmedge_tensorflow")]
     extern "C" {
+        pub fn version(arg0: i32) -> i32;
+        pub fn capabilities(arg0: i32) -> i32;
         pub fn create_session(arg0: i32, arg1: i32, arg2: i32) -> i32;
         pub fn create_session_saved_model(
             arg0: i32,
@@ -223,11 +422,20 @@ pub mod wasmedge_tensorflow {
             arg3: i32,
             arg4: i32,
         ) -> i32;
+        pub fn create_session_from_file(arg0: i32, arg1: i32, arg2: i32) -> i32;
         pub fn delete_session(arg0: i32) -> i32;
         pub fn run_session(arg0: i32) -> i32;
+        pub fn start_session(arg0: i32) -> i32;
+        pub fn poll_session(arg0: i32, arg1: i32) -> i32;
+        pub fn wait_session(arg0: i32, arg1: i32) -> i32;
         pub fn get_output_tensor(arg0: i32, arg1: i32, arg2: i32, arg3: i32) -> i32;
         pub fn get_tensor_len(arg0: i32, arg1: i32, arg2: i32) -> i32;
         pub fn get_tensor_data(arg0: i32, arg1: i32, arg2: i32, arg3: i32, arg4: i32) -> i32;
+        pub fn get_string_tensor_len(arg0: i32, arg1: i32, arg2: i32) -> i32;
+        pub fn get_string_tensor_data(arg0: i32, arg1: i32, arg2: i32, arg3: i32, arg4: i32)
+            -> i32;
+        pub fn get_tensor_dims_len(arg0: i32, arg1: i32, arg2: i32) -> i32;
+        pub fn get_tensor_dims(arg0: i32, arg1: i32, arg2: i32, arg3: i32, arg4: i32) -> i32;
         pub fn append_input(
             arg0: i32,
             arg1: i32,
@@ -238,6 +446,15 @@ pub mod wasmedge_tensorflow {
             arg6: i32,
             arg7: i32,
         ) -> i32;
+        pub fn append_string_input(
+            arg0: i32,
+            arg1: i32,
+            arg2: i32,
+            arg3: i32,
+            arg4: i32,
+            arg5: i32,
+            arg6: i32,
+        ) -> i32;
         pub fn append_output(arg0: i32, arg1: i32, arg2: i32) -> i32;
         pub fn clear_input(arg0: i32) -> i32;
         pub fn clear_output(arg0: i32) -> i32;
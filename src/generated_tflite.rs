@@ -26,7 +26,7 @@ impl WasmedgeTfliteErrno {
             3 => "MISSING_MEMORY",
             4 => "BUSY",
             5 => "RUNTIME_ERROR",
-            _ => unsafe { core::hint::unreachable_unchecked() },
+            _ => "UNKNOWN",
         }
     }
     pub fn message(&self) -> &'static str {
@@ -37,7 +37,7 @@ impl WasmedgeTfliteErrno {
             3 => "",
             4 => "",
             5 => "",
-            _ => unsafe { core::hint::unreachable_unchecked() },
+            _ => "",
         }
     }
 }
@@ -61,11 +61,71 @@ extern crate std;
 #[cfg(feature = "std")]
 impl std::error::Error for WasmedgeTfliteErrno {}
 
+pub type AbiVersion = u32;
+pub type CapabilityFlags = u32;
 pub type ModelBuffer<'a> = &'a [u8];
 pub type Session = u32;
+#[repr(transparent)]
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct SessionState(u32);
+pub const SESSION_STATE_RUNNING: SessionState = SessionState(0);
+pub const SESSION_STATE_DONE: SessionState = SessionState(1);
+pub const SESSION_STATE_FAILED: SessionState = SessionState(2);
+impl SessionState {
+    pub const fn raw(&self) -> u32 {
+        self.0
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self.0 {
+            0 => "RUNNING",
+            1 => "DONE",
+            2 => "FAILED",
+            _ => "UNKNOWN",
+        }
+    }
+    pub fn message(&self) -> &'static str {
+        match self.0 {
+            0 => "",
+            1 => "",
+            2 => "",
+            _ => "",
+        }
+    }
+}
+impl fmt::Debug for SessionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionState")
+            .field("code", &self.0)
+            .field("name", &self.name())
+            .field("message", &self.message())
+            .finish()
+    }
+}
+
 pub type Tensor = u32;
 pub type TensorSize = u32;
 pub type TensorData<'a> = &'a [u8];
+pub unsafe fn version() -> Result<AbiVersion, WasmedgeTfliteErrno> {
+    let mut rp0 = MaybeUninit::<AbiVersion>::uninit();
+    let ret = wasmedge_tensorflowlite::version(rp0.as_mut_ptr() as i32);
+    match ret {
+        0 => Ok(core::ptr::read(rp0.as_mut_ptr() as i32 as *const AbiVersion)),
+        _ => Err(WasmedgeTfliteErrno(ret as u32)),
+    }
+}
+
+pub unsafe fn capabilities() -> Result<CapabilityFlags, WasmedgeTfliteErrno> {
+    let mut rp0 = MaybeUninit::<CapabilityFlags>::uninit();
+    let ret = wasmedge_tensorflowlite::capabilities(rp0.as_mut_ptr() as i32);
+    match ret {
+        0 => Ok(core::ptr::read(
+            rp0.as_mut_ptr() as i32 as *const CapabilityFlags
+        )),
+        _ => Err(WasmedgeTfliteErrno(ret as u32)),
+    }
+}
+
 pub unsafe fn create_session(
     model_buffer: ModelBuffer<'_>,
 ) -> Result<Session, WasmedgeTfliteErrno> {
@@ -81,6 +141,19 @@ pub unsafe fn create_session(
     }
 }
 
+pub unsafe fn create_session_from_file(model_path: &str) -> Result<Session, WasmedgeTfliteErrno> {
+    let mut rp0 = MaybeUninit::<Session>::uninit();
+    let ret = wasmedge_tensorflowlite::create_session_from_file(
+        model_path.as_ptr() as i32,
+        model_path.len() as i32,
+        rp0.as_mut_ptr() as i32,
+    );
+    match ret {
+        0 => Ok(core::ptr::read(rp0.as_mut_ptr() as i32 as *const Session)),
+        _ => Err(WasmedgeTfliteErrno(ret as u32)),
+    }
+}
+
 pub unsafe fn delete_session(session: Session) -> Result<(), WasmedgeTfliteErrno> {
     let ret = wasmedge_tensorflowlite::delete_session(session as i32);
     match ret {
@@ -97,6 +170,36 @@ pub unsafe fn run_session(session: Session) -> Result<(), WasmedgeTfliteErrno> {
     }
 }
 
+pub unsafe fn start_session(session: Session) -> Result<(), WasmedgeTfliteErrno> {
+    let ret = wasmedge_tensorflowlite::start_session(session as i32);
+    match ret {
+        0 => Ok(()),
+        _ => Err(WasmedgeTfliteErrno(ret as u32)),
+    }
+}
+
+pub unsafe fn poll_session(session: Session) -> Result<SessionState, WasmedgeTfliteErrno> {
+    let mut rp0 = MaybeUninit::<SessionState>::uninit();
+    let ret = wasmedge_tensorflowlite::poll_session(session as i32, rp0.as_mut_ptr() as i32);
+    match ret {
+        0 => Ok(core::ptr::read(
+            rp0.as_mut_ptr() as i32 as *const SessionState
+        )),
+        _ => Err(WasmedgeTfliteErrno(ret as u32)),
+    }
+}
+
+pub unsafe fn wait_session(session: Session) -> Result<SessionState, WasmedgeTfliteErrno> {
+    let mut rp0 = MaybeUninit::<SessionState>::uninit();
+    let ret = wasmedge_tensorflowlite::wait_session(session as i32, rp0.as_mut_ptr() as i32);
+    match ret {
+        0 => Ok(core::ptr::read(
+            rp0.as_mut_ptr() as i32 as *const SessionState
+        )),
+        _ => Err(WasmedgeTfliteErrno(ret as u32)),
+    }
+}
+
 pub unsafe fn get_output_tensor(
     session: Session,
     name: &str,
@@ -150,6 +253,42 @@ pub unsafe fn get_tensor_data(
     }
 }
 
+pub unsafe fn get_tensor_dims_len(
+    session: Session,
+    tensor: Tensor,
+) -> Result<TensorSize, WasmedgeTfliteErrno> {
+    let mut rp0 = MaybeUninit::<TensorSize>::uninit();
+    let ret = wasmedge_tensorflowlite::get_tensor_dims_len(
+        session as i32,
+        tensor as i32,
+        rp0.as_mut_ptr() as i32,
+    );
+    match ret {
+        0 => Ok(core::ptr::read(rp0.as_mut_ptr() as i32 as *const TensorSize)),
+        _ => Err(WasmedgeTfliteErrno(ret as u32)),
+    }
+}
+
+pub unsafe fn get_tensor_dims(
+    session: Session,
+    tensor: Tensor,
+    dims_buf: *mut u64,
+    dims_buf_max_len: TensorSize,
+) -> Result<TensorSize, WasmedgeTfliteErrno> {
+    let mut rp0 = MaybeUninit::<TensorSize>::uninit();
+    let ret = wasmedge_tensorflowlite::get_tensor_dims(
+        session as i32,
+        tensor as i32,
+        dims_buf as i32,
+        dims_buf_max_len as i32,
+        rp0.as_mut_ptr() as i32,
+    );
+    match ret {
+        0 => Ok(core::ptr::read(rp0.as_mut_ptr() as i32 as *const TensorSize)),
+        _ => Err(WasmedgeTfliteErrno(ret as u32)),
+    }
+}
+
 pub unsafe fn append_input(
     session: Session,
     name: &str,
@@ -171,12 +310,20 @@ pub unsafe fn append_input(
 pub mod wasmedge_tensorflowlite {
     #[link(wasm_import_module = "wasmedge_tensorflowlite")]
     extern "C" {
+        pub fn version(arg0: i32) -> i32;
+        pub fn capabilities(arg0: i32) -> i32;
         pub fn create_session(arg0: i32, arg1: i32, arg2: i32) -> i32;
+        pub fn create_session_from_file(arg0: i32, arg1: i32, arg2: i32) -> i32;
         pub fn delete_session(arg0: i32) -> i32;
         pub fn run_session(arg0: i32) -> i32;
+        pub fn start_session(arg0: i32) -> i32;
+        pub fn poll_session(arg0: i32, arg1: i32) -> i32;
+        pub fn wait_session(arg0: i32, arg1: i32) -> i32;
         pub fn get_output_tensor(arg0: i32, arg1: i32, arg2: i32, arg3: i32) -> i32;
         pub fn get_tensor_len(arg0: i32, arg1: i32, arg2: i32) -> i32;
         pub fn get_tensor_data(arg0: i32, arg1: i32, arg2: i32, arg3: i32, arg4: i32) -> i32;
+        pub fn get_tensor_dims_len(arg0: i32, arg1: i32, arg2: i32) -> i32;
+        pub fn get_tensor_dims(arg0: i32, arg1: i32, arg2: i32, arg3: i32, arg4: i32) -> i32;
         pub fn append_input(arg0: i32, arg1: i32, arg2: i32, arg3: i32, arg4: i32) -> i32;
     }
 }
@@ -0,0 +1,203 @@
+//! Host plug-in version and capability detection.
+//!
+//! Each plug-in reports the version of the host ABI it implements and the
+//! optional host functions it provides, so that applications can check an
+//! installed plug-in up front and avoid the optional functions it lacks.
+//!
+//! ```rust, ignore
+//! use wasmedge_tensorflow_interface::host::{self, Capabilities};
+//!
+//! let host = host::tensorflow_lite().unwrap();
+//! if !host.is_compatible() {
+//!     panic!("unsupported WasmEdge-TensorFlowLite plug-in {}", host.version);
+//! }
+//! let mut session = if host.supports(Capabilities::FROM_FILE) {
+//!     TFLiteSession::from_file("model.tflite")
+//! } else {
+//!     TFLiteSession::new(&std::fs::read("model.tflite").unwrap())
+//! };
+//! ```
+//!
+//! This does not make applications degrade gracefully on older plug-ins: WASM
+//! imports cannot be optional, so a plug-in without the `version` and
+//! `capabilities` host functions fails to instantiate applications importing
+//! them, as it does for any other host function it lacks. The released plug-ins
+//! provide none of the host functions added by this crate, so the module is
+//! only built with the `extended-abi` feature, for plug-ins implementing this
+//! ABI. The capabilities then tell which of its optional features a plug-in
+//! actually supports.
+
+use crate::error::Result;
+use crate::{generated_img, generated_tf, generated_tflite};
+use std::fmt;
+use std::ops::BitOr;
+use std::sync::OnceLock;
+
+// The host ABI version implemented by this crate.
+pub const ABI_VERSION: Version = Version::new(1, 0);
+
+// A host ABI version. Minor versions only add host functions, which are
+// reported as capabilities.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl Version {
+    pub const fn new(major: u16, minor: u16) -> Version {
+        Version { major, minor }
+    }
+
+    // Decode the `major << 16 | minor` value reported by the host.
+    pub const fn from_raw(raw: u32) -> Version {
+        Version::new((raw >> 16) as u16, raw as u16)
+    }
+
+    pub const fn raw(self) -> u32 {
+        (self.major as u32) << 16 | self.minor as u32
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+// Set of optional host functions provided by a plug-in.
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Capabilities(u32);
+
+impl Capabilities {
+    // Loading models from a preopened path with `from_file`.
+    pub const FROM_FILE: Capabilities = Capabilities(1 << 0);
+    // Background runs with `start_session` and `poll_session`, used by `run_async`.
+    pub const ASYNC: Capabilities = Capabilities(1 << 1);
+    // Output tensor shapes with `get_tensor_dims`.
+    pub const TENSOR_DIMS: Capabilities = Capabilities(1 << 2);
+    // Saved-model signature lookup. Reserved: no plug-in sets it yet.
+    pub const SIGNATURES: Capabilities = Capabilities(1 << 3);
+    // Session options, such as the number of threads. Reserved: no plug-in sets it yet.
+    pub const OPTIONS: Capabilities = Capabilities(1 << 4);
+    // Image decoding with a resize mode other than stretching. Reserved: no
+    // plug-in sets it yet.
+    pub const RESIZE: Capabilities = Capabilities(1 << 5);
+
+    const NAMES: [(Capabilities, &'static str); 6] = [
+        (Capabilities::FROM_FILE, "from_file"),
+        (Capabilities::ASYNC, "async"),
+        (Capabilities::TENSOR_DIMS, "tensor_dims"),
+        (Capabilities::SIGNATURES, "signatures"),
+        (Capabilities::OPTIONS, "options"),
+        (Capabilities::RESIZE, "resize"),
+    ];
+
+    pub const fn empty() -> Capabilities {
+        Capabilities(0)
+    }
+
+    pub const fn from_bits(bits: u32) -> Capabilities {
+        Capabilities(bits)
+    }
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    // Whether all capabilities of `other` are in the set.
+    pub const fn contains(self, other: Capabilities) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    // Names of the known capabilities in the set.
+    pub fn names(self) -> Vec<&'static str> {
+        Capabilities::NAMES
+            .iter()
+            .filter(|(c, _)| self.contains(*c))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+impl BitOr for Capabilities {
+    type Output = Capabilities;
+
+    fn bitor(self, rhs: Capabilities) -> Capabilities {
+        Capabilities(self.0 | rhs.0)
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.names().join(", "))
+    }
+}
+
+// Version and capabilities reported by a plug-in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct HostInfo {
+    pub version: Version,
+    pub capabilities: Capabilities,
+}
+
+impl HostInfo {
+    // Whether the plug-in implements the host ABI of this crate.
+    pub fn is_compatible(&self) -> bool {
+        self.version.major == ABI_VERSION.major
+    }
+
+    pub fn supports(&self, capabilities: Capabilities) -> bool {
+        self.capabilities.contains(capabilities)
+    }
+}
+
+// Query the WasmEdge-TensorFlow plug-in.
+pub fn tensorflow() -> Result<HostInfo> {
+    unsafe {
+        Ok(HostInfo {
+            version: Version::from_raw(generated_tf::version()?),
+            capabilities: Capabilities(generated_tf::capabilities()?),
+        })
+    }
+}
+
+// Query the WasmEdge-TensorFlowLite plug-in.
+pub fn tensorflow_lite() -> Result<HostInfo> {
+    unsafe {
+        Ok(HostInfo {
+            version: Version::from_raw(generated_tflite::version()?),
+            capabilities: Capabilities(generated_tflite::capabilities()?),
+        })
+    }
+}
+
+// Query the WasmEdge-Image plug-in.
+pub fn image() -> Result<HostInfo> {
+    unsafe {
+        Ok(HostInfo {
+            version: Version::from_raw(generated_img::version()?),
+            capabilities: Capabilities(generated_img::capabilities()?),
+        })
+    }
+}
+
+// Capabilities of the plug-in, queried once. A plug-in failing the query is
+// assumed to provide no optional host functions.
+fn cached(cache: &OnceLock<Capabilities>, query: fn() -> Result<HostInfo>) -> Capabilities {
+    *cache.get_or_init(|| match query() {
+        Ok(info) if info.is_compatible() => info.capabilities,
+        _ => Capabilities::empty(),
+    })
+}
+
+pub(crate) fn tensorflow_supports(capabilities: Capabilities) -> bool {
+    static CACHE: OnceLock<Capabilities> = OnceLock::new();
+    cached(&CACHE, tensorflow).contains(capabilities)
+}
+
+#[cfg(not(feature = "wasi-nn"))]
+pub(crate) fn tensorflow_lite_supports(capabilities: Capabilities) -> bool {
+    static CACHE: OnceLock<Capabilities> = OnceLock::new();
+    cached(&CACHE, tensorflow_lite).contains(capabilities)
+}
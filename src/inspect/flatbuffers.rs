@@ -0,0 +1,227 @@
+//! Minimal reader of FlatBuffers tables.
+
+use super::{Error, Result};
+
+fn read<const N: usize>(buf: &[u8], pos: usize) -> Result<[u8; N]> {
+    pos.checked_add(N)
+        .and_then(|end| buf.get(pos..end))
+        .map(|b| b.try_into().unwrap())
+        .ok_or(Error::Malformed("flatbuffer offset out of bounds"))
+}
+
+fn read_u32(buf: &[u8], pos: usize) -> Result<u32> {
+    read::<4>(buf, pos).map(u32::from_le_bytes)
+}
+
+// Follow the `uoffset_t` stored at `pos`.
+fn indirect(buf: &[u8], pos: usize) -> Result<usize> {
+    pos.checked_add(read_u32(buf, pos)? as usize)
+        .ok_or(Error::Malformed("flatbuffer offset out of bounds"))
+}
+
+// A table of a flatbuffer.
+#[derive(Copy, Clone)]
+pub struct Table<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Table<'a> {
+    // The root table of a flatbuffer.
+    pub fn root(buf: &'a [u8]) -> Result<Table<'a>> {
+        Ok(Table {
+            buf,
+            pos: indirect(buf, 0)?,
+        })
+    }
+
+    // Position of field `index`, or `None` when the field is absent.
+    fn field(&self, index: usize) -> Result<Option<usize>> {
+        let soffset = i32::from_le_bytes(read::<4>(self.buf, self.pos)?);
+        let vtable = (self.pos as i64 - soffset as i64) as usize;
+        let vtable_len = u16::from_le_bytes(read::<2>(self.buf, vtable)?) as usize;
+        let entry = 4 + 2 * index;
+        if entry + 2 > vtable_len {
+            return Ok(None);
+        }
+        let offset = u16::from_le_bytes(read::<2>(self.buf, vtable + entry)?) as usize;
+        Ok((offset != 0).then_some(self.pos + offset))
+    }
+
+    pub fn u8(&self, index: usize, default: u8) -> Result<u8> {
+        match self.field(index)? {
+            Some(pos) => Ok(read::<1>(self.buf, pos)?[0]),
+            None => Ok(default),
+        }
+    }
+
+    pub fn u32(&self, index: usize, default: u32) -> Result<u32> {
+        match self.field(index)? {
+            Some(pos) => read_u32(self.buf, pos),
+            None => Ok(default),
+        }
+    }
+
+    pub fn i32(&self, index: usize, default: i32) -> Result<i32> {
+        self.u32(index, default as u32).map(|v| v as i32)
+    }
+
+    pub fn u64(&self, index: usize, default: u64) -> Result<u64> {
+        match self.field(index)? {
+            Some(pos) => read::<8>(self.buf, pos).map(u64::from_le_bytes),
+            None => Ok(default),
+        }
+    }
+
+    pub fn table(&self, index: usize) -> Result<Option<Table<'a>>> {
+        match self.field(index)? {
+            Some(pos) => Ok(Some(Table {
+                buf: self.buf,
+                pos: indirect(self.buf, pos)?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    // Bytes of a vector of `elem_size` byte elements, and the element count.
+    fn vector(&self, index: usize, elem_size: usize) -> Result<Option<(usize, usize)>> {
+        let Some(pos) = self.field(index)? else {
+            return Ok(None);
+        };
+        let start = indirect(self.buf, pos)?;
+        let len = read_u32(self.buf, start)? as usize;
+        let fits = len
+            .checked_mul(elem_size)
+            .and_then(|n| n.checked_add(start + 4))
+            .is_some_and(|end| end <= self.buf.len());
+        if !fits {
+            return Err(Error::Malformed("flatbuffer vector out of bounds"));
+        }
+        Ok(Some((start + 4, len)))
+    }
+
+    pub fn bytes(&self, index: usize) -> Result<&'a [u8]> {
+        match self.vector(index, 1)? {
+            Some((start, len)) => Ok(&self.buf[start..start + len]),
+            None => Ok(&[]),
+        }
+    }
+
+    pub fn string(&self, index: usize) -> Result<Option<String>> {
+        match self.field(index)? {
+            Some(_) => Ok(Some(
+                String::from_utf8_lossy(self.bytes(index)?).into_owned(),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    // A vector of 4-byte scalars, such as `[int]`, `[uint]` or `[float]`.
+    pub fn scalars32(&self, index: usize) -> Result<Vec<[u8; 4]>> {
+        match self.vector(index, 4)? {
+            Some((start, len)) => (0..len)
+                .map(|i| read::<4>(self.buf, start + 4 * i))
+                .collect(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn i32s(&self, index: usize) -> Result<Vec<i32>> {
+        Ok(self
+            .scalars32(index)?
+            .into_iter()
+            .map(i32::from_le_bytes)
+            .collect())
+    }
+
+    pub fn f32s(&self, index: usize) -> Result<Vec<f32>> {
+        Ok(self
+            .scalars32(index)?
+            .into_iter()
+            .map(f32::from_le_bytes)
+            .collect())
+    }
+
+    pub fn i64s(&self, index: usize) -> Result<Vec<i64>> {
+        match self.vector(index, 8)? {
+            Some((start, len)) => (0..len)
+                .map(|i| read::<8>(self.buf, start + 8 * i).map(i64::from_le_bytes))
+                .collect(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn tables(&self, index: usize) -> Result<Vec<Table<'a>>> {
+        match self.vector(index, 4)? {
+            Some((start, len)) => (0..len)
+                .map(|i| {
+                    Ok(Table {
+                        buf: self.buf,
+                        pos: indirect(self.buf, start + 4 * i)?,
+                    })
+                })
+                .collect(),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A root table at 8 with its vtable at 0: a `u32` field 0 of 7, an absent
+    // field 1 and a string field 2 pointing to "hi".
+    fn buf() -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend(8u32.to_le_bytes()); // root offset, overwritten below
+        buf.extend(10u16.to_le_bytes()); // vtable length
+        buf.extend(12u16.to_le_bytes()); // table length
+        buf.extend([4u16, 0, 8].iter().flat_map(|o| o.to_le_bytes()));
+        buf.extend((14i32 - 4).to_le_bytes()); // table at 14, vtable at 4
+        buf.extend(7u32.to_le_bytes());
+        buf.extend(4u32.to_le_bytes()); // string at 26
+        buf.extend(2u32.to_le_bytes());
+        buf.extend(b"hi");
+        buf[0..4].copy_from_slice(&14u32.to_le_bytes());
+        buf
+    }
+
+    #[test]
+    fn reads_fields() {
+        let buf = buf();
+        let table = Table::root(&buf).unwrap();
+        assert_eq!(table.u32(0, 0).unwrap(), 7);
+        assert_eq!(table.u32(1, 3).unwrap(), 3);
+        assert_eq!(table.string(2).unwrap().as_deref(), Some("hi"));
+        // Fields past the vtable are absent.
+        assert_eq!(table.string(5).unwrap(), None);
+    }
+
+    #[test]
+    fn out_of_bounds_offsets_are_errors() {
+        let buf = buf();
+        for len in 0..buf.len() {
+            // Every read either succeeds or fails, but never panics.
+            let Ok(table) = Table::root(&buf[..len]) else {
+                continue;
+            };
+            let _ = table.u32(0, 0);
+            assert!(table.string(2).is_err());
+        }
+
+        let mut bad = buf.clone();
+        bad[0..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(Table::root(&bad).unwrap().u32(0, 0).is_err());
+
+        // A vtable before the start of the buffer.
+        let mut bad = buf.clone();
+        bad[14..18].copy_from_slice(&100i32.to_le_bytes());
+        assert!(Table::root(&bad).unwrap().u32(0, 0).is_err());
+
+        // A string longer than the buffer.
+        let mut bad = buf;
+        bad[26..30].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(Table::root(&bad).unwrap().string(2).is_err());
+    }
+}
@@ -0,0 +1,240 @@
+//! Model inspection for the `tfinspect` binary.
+//!
+//! Reads frozen graphs (`.pb`), SavedModel directories and TensorFlow-Lite
+//! models natively, without the host plug-ins, and reports the inputs and
+//! outputs with their types and shapes, quantization parameters, op counts and
+//! embedded metadata. The `wasi-nn` backend also reads the tensor names of
+//! TensorFlow-Lite models with it.
+//!
+//! ```rust, ignore
+//! let info = wasmedge_tensorflow_interface::inspect::inspect_path("mobilenet_v2.tflite").unwrap();
+//! println!("{}", info);
+//! ```
+
+pub mod flatbuffers;
+pub mod protobuf;
+mod tensorflow;
+pub(crate) mod tflite;
+
+use crate::{DataType, InputSpec};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    // The model file is not valid.
+    Malformed(&'static str),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Malformed(msg) => write!(f, "malformed model: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+// The model formats.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    FrozenGraph,
+    SavedModel,
+    TFLite,
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Format::FrozenGraph => "TensorFlow frozen graph",
+            Format::SavedModel => "TensorFlow SavedModel",
+            Format::TFLite => "TensorFlow-Lite",
+        })
+    }
+}
+
+// Affine quantization of a tensor: `real = scale * (quantized - zero_point)`,
+// per tensor or per channel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quantization {
+    pub scale: Vec<f32>,
+    pub zero_point: Vec<i64>,
+    pub dimension: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TensorInfo {
+    // The name to pass to `add_input`, `add_output` and `get_output`.
+    pub name: String,
+    // Key of the tensor in its signature.
+    pub key: Option<String>,
+    // Element type, or `None` when it is unknown or has no `DataType`.
+    pub dtype: Option<DataType>,
+    // Type name of the model for the types without a `DataType`, such as int4.
+    pub raw_dtype: Option<String>,
+    // Dimensions, with -1 for unknown sizes, or `None` when the rank is unknown.
+    pub shape: Option<Vec<i64>>,
+    pub quantization: Option<Quantization>,
+}
+
+// A set of inputs and outputs: a SavedModel or TensorFlow-Lite signature, or a
+// graph without signatures.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signature {
+    pub name: String,
+    pub inputs: Vec<TensorInfo>,
+    pub outputs: Vec<TensorInfo>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelInfo {
+    pub format: Format,
+    pub signatures: Vec<Signature>,
+    // Number of nodes or operators of each op type.
+    pub op_counts: BTreeMap<String, usize>,
+    pub metadata: Vec<(String, String)>,
+}
+
+// Inspect a frozen graph or TensorFlow-Lite file, or a SavedModel directory.
+pub fn inspect_path<P: AsRef<Path>>(path: P) -> Result<ModelInfo> {
+    let path = path.as_ref();
+    if path.is_dir() {
+        return tensorflow::inspect_saved_model(&std::fs::read(path.join("saved_model.pb"))?);
+    }
+    inspect_bytes(&std::fs::read(path)?)
+}
+
+// Inspect a frozen graph, SavedModel protobuf or TensorFlow-Lite model in memory.
+pub fn inspect_bytes(buf: &[u8]) -> Result<ModelInfo> {
+    if tflite::is_tflite(buf) {
+        tflite::inspect(buf)
+    } else if tensorflow::is_saved_model(buf) {
+        tensorflow::inspect_saved_model(buf)
+    } else {
+        tensorflow::inspect_graph_def(buf)
+    }
+}
+
+impl TensorInfo {
+    // Name of the element type, as `DataType::name` or as the model names it.
+    pub fn dtype_name(&self) -> &str {
+        match (self.dtype, &self.raw_dtype) {
+            (Some(dtype), _) => dtype.name(),
+            (None, Some(raw)) => raw,
+            (None, None) => "unknown",
+        }
+    }
+
+    // The spec to register with `set_input_spec` for this input, or `None`
+    // when its element type is unknown.
+    pub fn input_spec(&self) -> Option<InputSpec> {
+        let dtype = self.dtype?;
+        Some(match &self.shape {
+            Some(shape) => InputSpec::new(dtype, shape),
+            None => InputSpec::with_any_shape(dtype),
+        })
+    }
+}
+
+impl fmt::Display for TensorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(key) = &self.key {
+            write!(f, "{} -> ", key)?;
+        }
+        write!(f, "{}: {} ", self.name, self.dtype_name())?;
+        match &self.shape {
+            Some(shape) => {
+                let dims: Vec<String> = shape
+                    .iter()
+                    .map(|d| {
+                        if *d < 0 {
+                            "?".to_string()
+                        } else {
+                            d.to_string()
+                        }
+                    })
+                    .collect();
+                write!(f, "[{}]", dims.join(", "))?;
+            }
+            None => f.write_str("<unknown rank>")?,
+        }
+        if let Some(q) = &self.quantization {
+            write!(f, " scale={:?} zero_point={:?}", q.scale, q.zero_point)?;
+            if q.scale.len() > 1 {
+                write!(f, " dimension={}", q.dimension)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ModelInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "format: {}", self.format)?;
+        for sig in &self.signatures {
+            writeln!(f, "signature: {}", sig.name)?;
+            writeln!(f, "  inputs:")?;
+            for t in &sig.inputs {
+                writeln!(f, "    {}", t)?;
+            }
+            writeln!(f, "  outputs:")?;
+            for t in &sig.outputs {
+                writeln!(f, "    {}", t)?;
+            }
+        }
+        let total: usize = self.op_counts.values().sum();
+        writeln!(f, "ops: {} ({} types)", total, self.op_counts.len())?;
+        for (op, count) in &self.op_counts {
+            writeln!(f, "  {}: {}", op, count)?;
+        }
+        if !self.metadata.is_empty() {
+            writeln!(f, "metadata:")?;
+            for (key, value) in &self.metadata {
+                writeln!(f, "  {}: {}", key, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_specs_of_inspected_inputs() {
+        let mut info = TensorInfo {
+            name: "input".to_string(),
+            key: None,
+            dtype: Some(DataType::Float),
+            raw_dtype: None,
+            shape: Some(vec![-1, 224, 224, 3]),
+            quantization: None,
+        };
+        let spec = info.input_spec().unwrap();
+        assert_eq!(spec, InputSpec::new(DataType::Float, &[-1, 224, 224, 3]));
+        assert!(spec.check(DataType::Float, &[2, 224, 224, 3]).is_ok());
+
+        info.shape = None;
+        assert_eq!(
+            info.input_spec(),
+            Some(InputSpec::with_any_shape(DataType::Float))
+        );
+
+        info.dtype = None;
+        info.raw_dtype = Some("int4".to_string());
+        assert_eq!(info.input_spec(), None);
+    }
+}
@@ -0,0 +1,167 @@
+//! Minimal reader of the protocol buffers wire format.
+
+use super::{Error, Result};
+
+// A field value, by wire type.
+#[derive(Copy, Clone, Debug)]
+pub enum Value<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+impl<'a> Value<'a> {
+    pub fn as_u64(self) -> u64 {
+        match self {
+            Value::Varint(v) | Value::Fixed64(v) => v,
+            Value::Fixed32(v) => v as u64,
+            Value::Bytes(_) => 0,
+        }
+    }
+
+    pub fn as_i64(self) -> i64 {
+        self.as_u64() as i64
+    }
+
+    pub fn as_bytes(self) -> &'a [u8] {
+        match self {
+            Value::Bytes(b) => b,
+            _ => &[],
+        }
+    }
+
+    pub fn as_string(self) -> String {
+        String::from_utf8_lossy(self.as_bytes()).into_owned()
+    }
+
+    // The fields of an embedded message.
+    pub fn fields(self) -> Fields<'a> {
+        Fields::new(self.as_bytes())
+    }
+}
+
+// Iterator over the `(field number, value)` pairs of a message.
+pub struct Fields<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Fields<'a> {
+    pub fn new(buf: &'a [u8]) -> Fields<'a> {
+        Fields { buf, pos: 0 }
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self
+                .buf
+                .get(self.pos)
+                .ok_or(Error::Malformed("truncated varint"))?;
+            self.pos += 1;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(Error::Malformed("varint too long"))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.buf.len())
+            .ok_or(Error::Malformed("truncated field"))?;
+        let bytes = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn field(&mut self) -> Result<(u32, Value<'a>)> {
+        let key = self.varint()?;
+        let value = match key & 7 {
+            0 => Value::Varint(self.varint()?),
+            1 => Value::Fixed64(u64::from_le_bytes(self.take(8)?.try_into().unwrap())),
+            2 => {
+                let len = self.varint()? as usize;
+                Value::Bytes(self.take(len)?)
+            }
+            5 => Value::Fixed32(u32::from_le_bytes(self.take(4)?.try_into().unwrap())),
+            _ => return Err(Error::Malformed("unsupported wire type")),
+        };
+        Ok(((key >> 3) as u32, value))
+    }
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = Result<(u32, Value<'a>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.buf.len() {
+            return None;
+        }
+        let field = self.field();
+        if field.is_err() {
+            // Stop after an error instead of reading garbage.
+            self.pos = self.buf.len();
+        }
+        Some(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(buf: &[u8]) -> Vec<Result<(u32, Value<'_>)>> {
+        Fields::new(buf).collect()
+    }
+
+    #[test]
+    fn reads_wire_types() {
+        let buf = [
+            0x08, 0x96, 0x01, // 1: varint 150
+            0x12, 0x02, b'h', b'i', // 2: bytes "hi"
+            0x1d, 1, 0, 0, 0, // 3: fixed32 1
+            0x21, 2, 0, 0, 0, 0, 0, 0, 0, // 4: fixed64 2
+        ];
+        let fields: Vec<_> = Fields::new(&buf).map(|f| f.unwrap()).collect();
+        assert_eq!(fields.len(), 4);
+        assert_eq!((fields[0].0, fields[0].1.as_u64()), (1, 150));
+        assert_eq!(
+            (fields[1].0, fields[1].1.as_string()),
+            (2, "hi".to_string())
+        );
+        assert_eq!((fields[2].0, fields[2].1.as_u64()), (3, 1));
+        assert_eq!((fields[3].0, fields[3].1.as_u64()), (4, 2));
+    }
+
+    #[test]
+    fn malformed_input_is_an_error() {
+        // Truncated varint, length past the end, truncated fixed64.
+        for buf in [&[0x08, 0x96][..], &[0x12, 0x05, b'h'], &[0x21, 1, 2]] {
+            let fields = fields(buf);
+            assert_eq!(fields.len(), 1, "{:?}", buf);
+            assert!(matches!(fields[0], Err(Error::Malformed(_))));
+        }
+        // Eleven continuation bytes.
+        assert!(matches!(
+            fields(&[0x08, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01])[0],
+            Err(Error::Malformed("varint too long"))
+        ));
+        // Group wire types are not supported, and reading stops at the error.
+        let fields = fields(&[0x0b, 0x08, 0x01]);
+        assert_eq!(fields.len(), 1);
+        assert!(matches!(
+            fields[0],
+            Err(Error::Malformed("unsupported wire type"))
+        ));
+        // A length that overflows the position.
+        let buf = [
+            0x12, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01,
+        ];
+        assert!(Fields::new(&buf).any(|f| f.is_err()));
+    }
+}
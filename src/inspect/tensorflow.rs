@@ -0,0 +1,518 @@
+//! Frozen graphs (`GraphDef`) and SavedModels (`SavedModel`).
+
+use super::protobuf::{Fields, Value};
+use super::{Format, ModelInfo, Result, Signature, TensorInfo};
+use crate::DataType;
+use std::collections::{BTreeMap, HashSet};
+
+// Ops that never produce model outputs.
+const NON_OUTPUT_OPS: [&str; 3] = ["Const", "NoOp", "Assert"];
+
+#[derive(Default)]
+struct Node {
+    name: String,
+    op: String,
+    inputs: Vec<String>,
+    attrs: Vec<(String, Vec<u8>)>,
+}
+
+impl Node {
+    fn attr(&self, key: &str) -> Option<Fields<'_>> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| Fields::new(v))
+    }
+
+    // The `type` of the first of the `keys` attributes that is set.
+    fn attr_type(&self, keys: &[&str]) -> Result<Option<u64>> {
+        for key in keys {
+            if let Some(attr) = self.attr(key) {
+                for field in attr {
+                    if let (6, v) = field? {
+                        return Ok(Some(v.as_u64()));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    // The `shape` attribute, or the first shape of `_output_shapes`.
+    fn attr_shape(&self) -> Result<Option<Vec<i64>>> {
+        if let Some(attr) = self.attr("shape") {
+            for field in attr {
+                if let (7, v) = field? {
+                    return shape(v);
+                }
+            }
+        }
+        if let Some(attr) = self.attr("_output_shapes") {
+            for field in attr {
+                if let (1, list) = field? {
+                    for field in list.fields() {
+                        if let (7, v) = field? {
+                            return shape(v);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn tensor_info(&self) -> Result<TensorInfo> {
+        let mut info = TensorInfo {
+            name: self.name.clone(),
+            key: None,
+            dtype: None,
+            raw_dtype: None,
+            shape: self.attr_shape()?,
+            quantization: None,
+        };
+        if let Some(value) = self.attr_type(&["dtype", "T", "out_type"])? {
+            set_dtype(&mut info, value);
+        }
+        Ok(info)
+    }
+}
+
+// Set the element type of a `DataType` value, where the `_REF` variants of
+// variables have the type of their values.
+fn set_dtype(info: &mut TensorInfo, value: u64) {
+    let base = if value > 100 { value - 100 } else { value };
+    info.dtype = u32::try_from(base).ok().and_then(DataType::from_value);
+    if info.dtype.is_none() {
+        info.raw_dtype = Some(format!("dtype({})", value));
+    }
+}
+
+// Dimensions of a `TensorShapeProto`.
+fn shape(proto: Value<'_>) -> Result<Option<Vec<i64>>> {
+    let mut dims = Vec::new();
+    for field in proto.fields() {
+        match field? {
+            (2, dim) => {
+                let mut size = 0;
+                for field in dim.fields() {
+                    if let (1, v) = field? {
+                        size = v.as_i64();
+                    }
+                }
+                dims.push(size);
+            }
+            (3, v) if v.as_u64() != 0 => return Ok(None),
+            _ => {}
+        }
+    }
+    Ok(Some(dims))
+}
+
+fn node(proto: Value<'_>) -> Result<Node> {
+    let mut node = Node::default();
+    for field in proto.fields() {
+        match field? {
+            (1, v) => node.name = v.as_string(),
+            (2, v) => node.op = v.as_string(),
+            (3, v) => node.inputs.push(v.as_string()),
+            (5, entry) => {
+                let mut key = String::new();
+                let mut value = Vec::new();
+                for field in entry.fields() {
+                    match field? {
+                        (1, v) => key = v.as_string(),
+                        (2, v) => value = v.as_bytes().to_vec(),
+                        _ => {}
+                    }
+                }
+                node.attrs.push((key, value));
+            }
+            _ => {}
+        }
+    }
+    Ok(node)
+}
+
+struct Graph {
+    nodes: Vec<Node>,
+    // Nodes of the functions of the graph library.
+    function_ops: Vec<String>,
+    producer: Option<i64>,
+}
+
+fn graph_def(buf: &[u8]) -> Result<Graph> {
+    let mut graph = Graph {
+        nodes: Vec::new(),
+        function_ops: Vec::new(),
+        producer: None,
+    };
+    for field in Fields::new(buf) {
+        match field? {
+            (1, v) => graph.nodes.push(node(v)?),
+            (2, library) => {
+                for field in library.fields() {
+                    if let (1, function) = field? {
+                        for field in function.fields() {
+                            if let (3, v) = field? {
+                                graph.function_ops.push(node(v)?.op);
+                            }
+                        }
+                    }
+                }
+            }
+            (4, versions) => {
+                for field in versions.fields() {
+                    if let (1, v) = field? {
+                        graph.producer = Some(v.as_i64());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(graph)
+}
+
+impl Graph {
+    fn op_counts(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        let ops = self.nodes.iter().map(|n| &n.op).chain(&self.function_ops);
+        for op in ops {
+            *counts.entry(op.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    // Placeholders as inputs, and nodes no other node consumes as outputs.
+    fn signature(&self) -> Result<Signature> {
+        let consumed: HashSet<&str> = self
+            .nodes
+            .iter()
+            .flat_map(|n| &n.inputs)
+            .map(|i| {
+                let i = i.trim_start_matches('^');
+                i.split(':').next().unwrap_or(i)
+            })
+            .collect();
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+        for node in &self.nodes {
+            if node.op == "Placeholder" {
+                inputs.push(node.tensor_info()?);
+            } else if !consumed.contains(node.name.as_str())
+                && !NON_OUTPUT_OPS.contains(&node.op.as_str())
+            {
+                outputs.push(node.tensor_info()?);
+            }
+        }
+        Ok(Signature {
+            name: "graph".to_string(),
+            inputs,
+            outputs,
+        })
+    }
+}
+
+pub fn inspect_graph_def(buf: &[u8]) -> Result<ModelInfo> {
+    let graph = graph_def(buf)?;
+    let mut metadata = Vec::new();
+    if let Some(producer) = graph.producer {
+        metadata.push(("producer".to_string(), producer.to_string()));
+    }
+    Ok(ModelInfo {
+        format: Format::FrozenGraph,
+        signatures: vec![graph.signature()?],
+        op_counts: graph.op_counts(),
+        metadata,
+    })
+}
+
+// A `SavedModel` starts with its schema version or its first meta graph,
+// while a `GraphDef` starts with a node.
+pub fn is_saved_model(buf: &[u8]) -> bool {
+    matches!(
+        Fields::new(buf).next(),
+        Some(Ok((1, Value::Varint(_))) | Ok((2, _)))
+    )
+}
+
+// A `TensorInfo` of a `SignatureDef`.
+fn signature_tensor(key: String, proto: Value<'_>) -> Result<TensorInfo> {
+    let mut info = TensorInfo {
+        name: key.clone(),
+        key: Some(key),
+        dtype: None,
+        raw_dtype: None,
+        shape: None,
+        quantization: None,
+    };
+    for field in proto.fields() {
+        match field? {
+            (1, v) => info.name = v.as_string(),
+            (2, v) => set_dtype(&mut info, v.as_u64()),
+            (3, v) => info.shape = shape(v)?,
+            _ => {}
+        }
+    }
+    Ok(info)
+}
+
+// The `TensorInfo` values of a `map<string, TensorInfo>` entry.
+fn map_entry(entry: Value<'_>) -> Result<TensorInfo> {
+    let mut key = String::new();
+    let mut value = None;
+    for field in entry.fields() {
+        match field? {
+            (1, v) => key = v.as_string(),
+            (2, v) => value = Some(v),
+            _ => {}
+        }
+    }
+    signature_tensor(key, value.unwrap_or(Value::Bytes(&[])))
+}
+
+fn signature_def(entry: Value<'_>) -> Result<Signature> {
+    let mut sig = Signature {
+        name: String::new(),
+        inputs: Vec::new(),
+        outputs: Vec::new(),
+    };
+    for field in entry.fields() {
+        match field? {
+            (1, v) => sig.name = v.as_string(),
+            (2, def) => {
+                for field in def.fields() {
+                    match field? {
+                        (1, v) => sig.inputs.push(map_entry(v)?),
+                        (2, v) => sig.outputs.push(map_entry(v)?),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(sig)
+}
+
+pub fn inspect_saved_model(buf: &[u8]) -> Result<ModelInfo> {
+    let mut info = ModelInfo {
+        format: Format::SavedModel,
+        signatures: Vec::new(),
+        op_counts: BTreeMap::new(),
+        metadata: Vec::new(),
+    };
+    for field in Fields::new(buf) {
+        match field? {
+            (1, v) => info
+                .metadata
+                .push(("schema_version".to_string(), v.as_u64().to_string())),
+            (2, meta_graph) => {
+                let mut graph = None;
+                let mut tags = Vec::new();
+                for field in meta_graph.fields() {
+                    match field? {
+                        (1, meta_info) => {
+                            for field in meta_info.fields() {
+                                match field? {
+                                    (4, v) => tags.push(v.as_string()),
+                                    (5, v) => info
+                                        .metadata
+                                        .push(("tensorflow_version".to_string(), v.as_string())),
+                                    _ => {}
+                                }
+                            }
+                        }
+                        (2, v) => graph = Some(graph_def(v.as_bytes())?),
+                        (5, v) => info.signatures.push(signature_def(v)?),
+                        _ => {}
+                    }
+                }
+                info.metadata.push(("tags".to_string(), tags.join(",")));
+                if let Some(graph) = graph {
+                    for (op, count) in graph.op_counts() {
+                        *info.op_counts.entry(op).or_insert(0) += count;
+                    }
+                    if info.signatures.is_empty() {
+                        info.signatures.push(graph.signature()?);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{inspect_bytes, Error};
+    use super::*;
+
+    fn varint(out: &mut Vec<u8>, mut v: u64) {
+        while v >= 0x80 {
+            out.push(v as u8 | 0x80);
+            v >>= 7;
+        }
+        out.push(v as u8);
+    }
+
+    fn int(field: u32, v: i64) -> Vec<u8> {
+        let mut out = Vec::new();
+        varint(&mut out, (field as u64) << 3);
+        varint(&mut out, v as u64);
+        out
+    }
+
+    fn msg(field: u32, parts: &[Vec<u8>]) -> Vec<u8> {
+        let body = parts.concat();
+        let mut out = Vec::new();
+        varint(&mut out, (field as u64) << 3 | 2);
+        varint(&mut out, body.len() as u64);
+        out.extend(body);
+        out
+    }
+
+    fn text(field: u32, s: &str) -> Vec<u8> {
+        msg(field, &[s.as_bytes().to_vec()])
+    }
+
+    fn shape_proto(field: u32, dims: &[i64]) -> Vec<u8> {
+        let dims: Vec<_> = dims.iter().map(|d| msg(2, &[int(1, *d)])).collect();
+        msg(field, &dims)
+    }
+
+    fn node(name: &str, op: &str, inputs: &[&str], attrs: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut parts = vec![text(1, name), text(2, op)];
+        parts.extend(inputs.iter().map(|i| text(3, i)));
+        parts.extend(
+            attrs
+                .iter()
+                .map(|(key, value)| msg(5, &[text(1, key), msg(2, std::slice::from_ref(value))])),
+        );
+        msg(1, &parts)
+    }
+
+    fn graph_def() -> Vec<u8> {
+        [
+            node(
+                "input",
+                "Placeholder",
+                &[],
+                &[
+                    ("dtype", int(6, 1)),
+                    ("shape", shape_proto(7, &[-1, 224, 224, 3])),
+                ],
+            ),
+            node("weights", "Const", &[], &[("dtype", int(6, 1))]),
+            node("conv", "Conv2D", &["input", "weights"], &[("T", int(6, 1))]),
+            node("probs", "Softmax", &["conv:0"], &[("T", int(6, 101))]),
+            node("init", "NoOp", &["^weights"], &[]),
+            msg(4, &[int(1, 1234)]),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn graph_def_placeholders_and_outputs() {
+        let info = inspect_bytes(&graph_def()).unwrap();
+        assert_eq!(info.format, Format::FrozenGraph);
+        let sig = &info.signatures[0];
+        assert_eq!(sig.inputs.len(), 1);
+        assert_eq!(sig.inputs[0].name, "input");
+        assert_eq!(sig.inputs[0].dtype, Some(DataType::Float));
+        assert_eq!(sig.inputs[0].shape, Some(vec![-1, 224, 224, 3]));
+        // Consumed nodes, constants and no-ops are not outputs.
+        assert_eq!(sig.outputs.len(), 1);
+        assert_eq!(sig.outputs[0].name, "probs");
+        assert_eq!(sig.outputs[0].dtype, Some(DataType::Float));
+        assert_eq!(sig.outputs[0].shape, None);
+        assert_eq!(info.op_counts.len(), 5);
+        assert_eq!(info.op_counts["Conv2D"], 1);
+        assert_eq!(
+            info.metadata,
+            [("producer".to_string(), "1234".to_string())]
+        );
+    }
+
+    #[test]
+    fn unknown_rank() {
+        let unknown = msg(7, &[int(3, 1)]);
+        let buf = node("x", "Placeholder", &[], &[("shape", unknown)]);
+        let info = inspect_graph_def(&buf).unwrap();
+        assert_eq!(info.signatures[0].inputs[0].shape, None);
+        assert_eq!(info.signatures[0].inputs[0].dtype, None);
+        assert_eq!(info.signatures[0].inputs[0].dtype_name(), "unknown");
+    }
+
+    #[test]
+    fn saved_model_signatures() {
+        let tensor = |key: &str, name: &str, dims: &[i64]| {
+            msg(
+                1,
+                &[
+                    text(1, key),
+                    msg(2, &[text(1, name), int(2, 3), shape_proto(3, dims)]),
+                ],
+            )
+        };
+        let mut output = tensor("y", "StatefulPartitionedCall:0", &[-1, 10]);
+        output[0] = 0x12; // field 2 of the SignatureDef
+        let signature = msg(
+            5,
+            &[
+                text(1, "serving_default"),
+                msg(2, &[tensor("x", "serving_default_x:0", &[-1, 4]), output]),
+            ],
+        );
+        let meta_graph = msg(
+            2,
+            &[
+                msg(1, &[text(4, "serve"), text(5, "2.15.0")]),
+                msg(2, &[graph_def()]),
+                signature,
+            ],
+        );
+        let buf = [int(1, 1), meta_graph].concat();
+
+        assert!(is_saved_model(&buf));
+        assert!(!is_saved_model(&graph_def()));
+        let info = inspect_bytes(&buf).unwrap();
+        assert_eq!(info.format, Format::SavedModel);
+        assert_eq!(info.signatures.len(), 1);
+        let sig = &info.signatures[0];
+        assert_eq!(sig.name, "serving_default");
+        assert_eq!(sig.inputs[0].key.as_deref(), Some("x"));
+        assert_eq!(sig.inputs[0].name, "serving_default_x:0");
+        assert_eq!(sig.inputs[0].dtype, Some(DataType::Int32));
+        assert_eq!(sig.inputs[0].shape, Some(vec![-1, 4]));
+        assert_eq!(sig.outputs[0].key.as_deref(), Some("y"));
+        assert_eq!(sig.outputs[0].shape, Some(vec![-1, 10]));
+        assert_eq!(info.op_counts["Placeholder"], 1);
+        assert_eq!(
+            info.metadata,
+            [
+                ("schema_version".to_string(), "1".to_string()),
+                ("tensorflow_version".to_string(), "2.15.0".to_string()),
+                ("tags".to_string(), "serve".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_and_malformed_input_is_an_error() {
+        let buf = graph_def();
+        // Cutting the graph anywhere but between nodes leaves a truncated field.
+        for len in 1..buf.len() {
+            let _ = inspect_bytes(&buf[..len]);
+        }
+        assert!(matches!(
+            inspect_graph_def(&buf[..buf.len() - 1]),
+            Err(Error::Malformed(_))
+        ));
+        assert!(inspect_bytes(&[0xff; 16]).is_err());
+        // A node with a malformed attribute.
+        let bad = msg(1, &[text(1, "x"), msg(5, &[vec![0x0f]])]);
+        assert!(inspect_graph_def(&bad).is_err());
+    }
+}
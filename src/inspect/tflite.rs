@@ -0,0 +1,639 @@
+//! TensorFlow-Lite flatbuffer models, following the TensorFlow-Lite `schema.fbs`.
+
+use super::flatbuffers::Table;
+use super::{Format, ModelInfo, Quantization, Result, Signature, TensorInfo};
+use crate::DataType;
+use std::collections::BTreeMap;
+
+// The `DataType` of the `TensorType` values, or their name when TensorFlow has
+// no such type.
+const TENSOR_TYPES: [std::result::Result<DataType, &str>; 19] = [
+    Ok(DataType::Float),
+    Ok(DataType::Half),
+    Ok(DataType::Int32),
+    Ok(DataType::UInt8),
+    Ok(DataType::Int64),
+    Ok(DataType::String),
+    Ok(DataType::Bool),
+    Ok(DataType::Int16),
+    Ok(DataType::Complex64),
+    Ok(DataType::Int8),
+    Ok(DataType::Double),
+    Ok(DataType::Complex128),
+    Ok(DataType::UInt64),
+    Ok(DataType::Resource),
+    Ok(DataType::Variant),
+    Ok(DataType::UInt32),
+    Ok(DataType::UInt16),
+    Err("int4"),
+    Ok(DataType::BFloat16),
+];
+
+// Names of the `BuiltinOperator` values.
+const BUILTIN_OPS: [&str; 162] = [
+    "ADD",
+    "AVERAGE_POOL_2D",
+    "CONCATENATION",
+    "CONV_2D",
+    "DEPTHWISE_CONV_2D",
+    "DEPTH_TO_SPACE",
+    "DEQUANTIZE",
+    "EMBEDDING_LOOKUP",
+    "FLOOR",
+    "FULLY_CONNECTED",
+    "HASHTABLE_LOOKUP",
+    "L2_NORMALIZATION",
+    "L2_POOL_2D",
+    "LOCAL_RESPONSE_NORMALIZATION",
+    "LOGISTIC",
+    "LSH_PROJECTION",
+    "LSTM",
+    "MAX_POOL_2D",
+    "MUL",
+    "RELU",
+    "RELU_N1_TO_1",
+    "RELU6",
+    "RESHAPE",
+    "RESIZE_BILINEAR",
+    "RNN",
+    "SOFTMAX",
+    "SPACE_TO_DEPTH",
+    "SVDF",
+    "TANH",
+    "CONCAT_EMBEDDINGS",
+    "SKIP_GRAM",
+    "CALL",
+    "CUSTOM",
+    "EMBEDDING_LOOKUP_SPARSE",
+    "PAD",
+    "UNIDIRECTIONAL_SEQUENCE_RNN",
+    "GATHER",
+    "BATCH_TO_SPACE_ND",
+    "SPACE_TO_BATCH_ND",
+    "TRANSPOSE",
+    "MEAN",
+    "SUB",
+    "DIV",
+    "SQUEEZE",
+    "UNIDIRECTIONAL_SEQUENCE_LSTM",
+    "STRIDED_SLICE",
+    "BIDIRECTIONAL_SEQUENCE_RNN",
+    "EXP",
+    "TOPK_V2",
+    "SPLIT",
+    "LOG_SOFTMAX",
+    "DELEGATE",
+    "BIDIRECTIONAL_SEQUENCE_LSTM",
+    "CAST",
+    "PRELU",
+    "MAXIMUM",
+    "ARG_MAX",
+    "MINIMUM",
+    "LESS",
+    "NEG",
+    "PADV2",
+    "GREATER",
+    "GREATER_EQUAL",
+    "LESS_EQUAL",
+    "SELECT",
+    "SLICE",
+    "SIN",
+    "TRANSPOSE_CONV",
+    "SPARSE_TO_DENSE",
+    "TILE",
+    "EXPAND_DIMS",
+    "EQUAL",
+    "NOT_EQUAL",
+    "LOG",
+    "SUM",
+    "SQRT",
+    "RSQRT",
+    "SHAPE",
+    "POW",
+    "ARG_MIN",
+    "FAKE_QUANT",
+    "REDUCE_PROD",
+    "REDUCE_MAX",
+    "PACK",
+    "LOGICAL_OR",
+    "ONE_HOT",
+    "LOGICAL_AND",
+    "LOGICAL_NOT",
+    "UNPACK",
+    "REDUCE_MIN",
+    "FLOOR_DIV",
+    "REDUCE_ANY",
+    "SQUARE",
+    "ZEROS_LIKE",
+    "FILL",
+    "FLOOR_MOD",
+    "RANGE",
+    "RESIZE_NEAREST_NEIGHBOR",
+    "LEAKY_RELU",
+    "SQUARED_DIFFERENCE",
+    "MIRROR_PAD",
+    "ABS",
+    "SPLIT_V",
+    "UNIQUE",
+    "CEIL",
+    "REVERSE_V2",
+    "ADD_N",
+    "GATHER_ND",
+    "COS",
+    "WHERE",
+    "RANK",
+    "ELU",
+    "REVERSE_SEQUENCE",
+    "MATRIX_DIAG",
+    "QUANTIZE",
+    "MATRIX_SET_DIAG",
+    "ROUND",
+    "HARD_SWISH",
+    "IF",
+    "WHILE",
+    "NON_MAX_SUPPRESSION_V4",
+    "NON_MAX_SUPPRESSION_V5",
+    "SCATTER_ND",
+    "SELECT_V2",
+    "DENSIFY",
+    "SEGMENT_SUM",
+    "BATCH_MATMUL",
+    "PLACEHOLDER_FOR_GREATER_OP_CODES",
+    "CUMSUM",
+    "CALL_ONCE",
+    "BROADCAST_TO",
+    "RFFT2D",
+    "CONV_3D",
+    "IMAG",
+    "REAL",
+    "COMPLEX_ABS",
+    "HASHTABLE",
+    "HASHTABLE_FIND",
+    "HASHTABLE_IMPORT",
+    "HASHTABLE_SIZE",
+    "REDUCE_ALL",
+    "CONV_3D_TRANSPOSE",
+    "VAR_HANDLE",
+    "READ_VARIABLE",
+    "ASSIGN_VARIABLE",
+    "BROADCAST_ARGS",
+    "RANDOM_STANDARD_NORMAL",
+    "BUCKETIZE",
+    "RANDOM_UNIFORM",
+    "MULTINOMIAL",
+    "GELU",
+    "DYNAMIC_UPDATE_SLICE",
+    "RELU_0_TO_1",
+    "UNSORTED_SEGMENT_PROD",
+    "UNSORTED_SEGMENT_MAX",
+    "UNSORTED_SEGMENT_SUM",
+    "ATAN2",
+    "UNSORTED_SEGMENT_MIN",
+    "SIGN",
+    "BITCAST",
+    "BITWISE_XOR",
+    "RIGHT_SHIFT",
+];
+
+// Builtin code of the custom operators.
+const CUSTOM_OP: i32 = 32;
+
+// TensorFlow-Lite models carry the `TFL3` file identifier.
+pub fn is_tflite(buf: &[u8]) -> bool {
+    buf.get(4..8) == Some(b"TFL3")
+}
+
+fn tensor_info(tensor: &Table<'_>) -> Result<TensorInfo> {
+    let dtype = tensor.u8(1, 0)?;
+    // `shape_signature` marks dynamic dimensions with -1.
+    let mut shape = tensor.i32s(7)?;
+    if shape.is_empty() {
+        shape = tensor.i32s(0)?;
+    }
+    let quantization = match tensor.table(4)? {
+        Some(q) => {
+            let scale = q.f32s(2)?;
+            let zero_point = q.i64s(3)?;
+            (!scale.is_empty()).then(|| Quantization {
+                scale,
+                zero_point,
+                dimension: q.i32(6, 0).unwrap_or(0),
+            })
+        }
+        None => None,
+    };
+    let (dtype, raw_dtype) = match TENSOR_TYPES.get(dtype as usize) {
+        Some(Ok(t)) => (Some(*t), None),
+        Some(Err(name)) => (None, Some(name.to_string())),
+        None => (None, Some(format!("type({})", dtype))),
+    };
+    Ok(TensorInfo {
+        name: tensor.string(3)?.unwrap_or_default(),
+        key: None,
+        dtype,
+        raw_dtype,
+        shape: Some(shape.into_iter().map(i64::from).collect()),
+        quantization,
+    })
+}
+
+// Names of the inputs and outputs of the main subgraph, in the order of their
+// indices, which the WASI-NN TensorFlow-Lite backend addresses them by.
+#[cfg(feature = "wasi-nn")]
+pub fn tensor_names(buf: &[u8]) -> Result<(Vec<String>, Vec<String>)> {
+    let model = Table::root(buf)?;
+    let Some(subgraph) = model.tables(2)?.into_iter().next() else {
+        return Ok((Vec::new(), Vec::new()));
+    };
+    let tensors = subgraph.tables(0)?;
+    let names = |index| -> Result<Vec<String>> {
+        subgraph
+            .i32s(index)?
+            .iter()
+            .map(|i| match tensors.get(*i as usize) {
+                Some(tensor) => Ok(tensor.string(3)?.unwrap_or_default()),
+                None => Err(super::Error::Malformed("tensor index out of range")),
+            })
+            .collect()
+    };
+    Ok((names(1)?, names(2)?))
+}
+
+fn tensors_at(tensors: &[TensorInfo], indices: &[i32]) -> Vec<TensorInfo> {
+    indices
+        .iter()
+        .filter_map(|i| tensors.get(*i as usize).cloned())
+        .collect()
+}
+
+fn op_name(code: &Table<'_>) -> Result<String> {
+    let builtin = (code.u8(0, 0)? as i8 as i32).max(code.i32(3, 0)?);
+    if builtin == CUSTOM_OP {
+        if let Some(custom) = code.string(1)? {
+            return Ok(custom);
+        }
+    }
+    Ok(BUILTIN_OPS
+        .get(builtin as usize)
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| format!("BUILTIN_{}", builtin)))
+}
+
+// A metadata buffer printed as text when it is a short printable string.
+fn metadata_value(data: &[u8]) -> String {
+    let text = std::str::from_utf8(data).map(|s| s.trim_end_matches('\0'));
+    match text {
+        Ok(s) if s.len() <= 256 && !s.chars().any(|c| c.is_control()) => s.to_string(),
+        _ => format!("<{} bytes>", data.len()),
+    }
+}
+
+pub fn inspect(buf: &[u8]) -> Result<ModelInfo> {
+    let model = Table::root(buf)?;
+    let op_names = model
+        .tables(1)?
+        .iter()
+        .map(op_name)
+        .collect::<Result<Vec<_>>>()?;
+    let subgraphs = model.tables(2)?;
+    let buffers = model.tables(4)?;
+
+    let mut subgraph_tensors = Vec::new();
+    let mut signatures = Vec::new();
+    let mut op_counts = BTreeMap::new();
+    for (i, subgraph) in subgraphs.iter().enumerate() {
+        let tensors = subgraph
+            .tables(0)?
+            .iter()
+            .map(tensor_info)
+            .collect::<Result<Vec<_>>>()?;
+        for op in subgraph.tables(3)? {
+            let name = op_names
+                .get(op.u32(0, 0)? as usize)
+                .cloned()
+                .unwrap_or_else(|| "UNKNOWN".to_string());
+            *op_counts.entry(name).or_insert(0) += 1;
+        }
+        let name = match subgraph.string(4)? {
+            Some(name) if !name.is_empty() => name,
+            _ => format!("subgraph {}", i),
+        };
+        signatures.push(Signature {
+            name,
+            inputs: tensors_at(&tensors, &subgraph.i32s(1)?),
+            outputs: tensors_at(&tensors, &subgraph.i32s(2)?),
+        });
+        subgraph_tensors.push(tensors);
+    }
+
+    // Signature definitions replace the raw subgraph inputs and outputs.
+    let signature_defs = model.tables(7)?;
+    if !signature_defs.is_empty() {
+        signatures.clear();
+        for def in signature_defs {
+            let Some(tensors) = subgraph_tensors.get(def.u32(4, 0)? as usize) else {
+                continue;
+            };
+            let map = |index| -> Result<Vec<TensorInfo>> {
+                def.tables(index)?
+                    .iter()
+                    .filter_map(|m| match (m.string(0), m.u32(1, 0)) {
+                        (Ok(key), Ok(t)) => tensors.get(t as usize).map(|info| {
+                            Ok(TensorInfo {
+                                key,
+                                ..info.clone()
+                            })
+                        }),
+                        (Err(e), _) | (_, Err(e)) => Some(Err(e)),
+                    })
+                    .collect()
+            };
+            signatures.push(Signature {
+                name: def.string(2)?.unwrap_or_default(),
+                inputs: map(0)?,
+                outputs: map(1)?,
+            });
+        }
+    }
+
+    let mut metadata = vec![("schema_version".to_string(), model.u32(0, 0)?.to_string())];
+    if let Some(description) = model.string(3)? {
+        metadata.push(("description".to_string(), description));
+    }
+    for entry in model.tables(6)? {
+        let name = entry.string(0)?.unwrap_or_default();
+        let value = match buffers.get(entry.u32(1, 0)? as usize) {
+            Some(buffer) => metadata_value(buffer.bytes(0)?),
+            None => "<missing buffer>".to_string(),
+        };
+        metadata.push((name, value));
+    }
+
+    Ok(ModelInfo {
+        format: Format::TFLite,
+        signatures,
+        op_counts,
+        metadata,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{inspect_bytes, Error};
+    use super::*;
+
+    // A field of a table being built, by type.
+    enum Field {
+        U8(u8),
+        U32(u32),
+        Str(&'static str),
+        Bytes(&'static [u8]),
+        I32s(Vec<i32>),
+        F32s(Vec<f32>),
+        I64s(Vec<i64>),
+        Table(Vec<(usize, Field)>),
+        Tables(Vec<Vec<(usize, Field)>>),
+    }
+
+    use Field::*;
+
+    fn patch(buf: &mut [u8], slot: usize, target: usize) {
+        buf[slot..slot + 4].copy_from_slice(&((target - slot) as u32).to_le_bytes());
+    }
+
+    fn vector(buf: &mut Vec<u8>, len: usize, elems: impl IntoIterator<Item = u8>) -> usize {
+        let pos = buf.len();
+        buf.extend((len as u32).to_le_bytes());
+        buf.extend(elems);
+        pos
+    }
+
+    fn write(buf: &mut Vec<u8>, field: &Field) -> usize {
+        match field {
+            Str(s) => vector(buf, s.len(), s.bytes()),
+            Bytes(b) => vector(buf, b.len(), b.iter().copied()),
+            I32s(v) => vector(buf, v.len(), v.iter().flat_map(|x| x.to_le_bytes())),
+            F32s(v) => vector(buf, v.len(), v.iter().flat_map(|x| x.to_le_bytes())),
+            I64s(v) => vector(buf, v.len(), v.iter().flat_map(|x| x.to_le_bytes())),
+            Table(fields) => table(buf, fields),
+            Tables(tables) => {
+                let pos = vector(buf, tables.len(), vec![0; 4 * tables.len()]);
+                for (i, fields) in tables.iter().enumerate() {
+                    let target = table(buf, fields);
+                    patch(buf, pos + 4 + 4 * i, target);
+                }
+                pos
+            }
+            U8(_) | U32(_) => unreachable!(),
+        }
+    }
+
+    // A table with its vtable in front and a 4-byte slot for every field,
+    // followed by the data its fields point to.
+    fn table(buf: &mut Vec<u8>, fields: &[(usize, Field)]) -> usize {
+        let count = fields.iter().map(|(i, _)| i + 1).max().unwrap_or(0);
+        let mut offsets = vec![0u16; count];
+        for (slot, (i, _)) in fields.iter().enumerate() {
+            offsets[*i] = 4 + 4 * slot as u16;
+        }
+        let vtable = buf.len();
+        buf.extend((4 + 2 * count as u16).to_le_bytes());
+        buf.extend((4 + 4 * fields.len() as u16).to_le_bytes());
+        buf.extend(offsets.iter().flat_map(|o| o.to_le_bytes()));
+        let pos = buf.len();
+        buf.extend(((pos - vtable) as i32).to_le_bytes());
+        let mut refs = Vec::new();
+        for (_, field) in fields {
+            match field {
+                U8(v) => buf.extend([*v, 0, 0, 0]),
+                U32(v) => buf.extend(v.to_le_bytes()),
+                _ => {
+                    refs.push((buf.len(), field));
+                    buf.extend([0; 4]);
+                }
+            }
+        }
+        for (slot, field) in refs {
+            let target = write(buf, field);
+            patch(buf, slot, target);
+        }
+        pos
+    }
+
+    fn model(fields: Vec<(usize, Field)>) -> Vec<u8> {
+        let mut buf = vec![0; 4];
+        buf.extend(b"TFL3");
+        let root = table(&mut buf, &fields);
+        buf[0..4].copy_from_slice(&(root as u32).to_le_bytes());
+        buf
+    }
+
+    fn model_fields() -> Vec<(usize, Field)> {
+        let input = vec![
+            (0, I32s(vec![1, 224, 224, 3])),
+            (1, U8(9)),
+            (3, Str("input")),
+            (4, Table(vec![(2, F32s(vec![0.5])), (3, I64s(vec![-128]))])),
+            (7, I32s(vec![-1, 224, 224, 3])),
+        ];
+        let output = vec![(0, I32s(vec![1, 10])), (1, U8(0)), (3, Str("output"))];
+        let op = |index| vec![(0, U32(index))];
+        vec![
+            (0, U32(3)),
+            (
+                1,
+                Tables(vec![
+                    vec![(0, U8(3))],
+                    vec![(0, U8(32)), (1, Str("MyOp"))],
+                    vec![(0, U8(0)), (3, U32(9))],
+                ]),
+            ),
+            (
+                2,
+                Tables(vec![vec![
+                    (0, Tables(vec![input, output])),
+                    (1, I32s(vec![0])),
+                    (2, I32s(vec![1, 7])),
+                    (3, Tables(vec![op(0), op(1), op(2), op(0)])),
+                    (4, Str("main")),
+                ]]),
+            ),
+            (3, Str("test model")),
+            (4, Tables(vec![vec![], vec![(0, Bytes(b"1.5.0\0"))]])),
+            (
+                6,
+                Tables(vec![vec![(0, Str("min_runtime_version")), (1, U32(1))]]),
+            ),
+        ]
+    }
+
+    #[test]
+    fn subgraph_inputs_outputs_and_ops() {
+        let buf = model(model_fields());
+        assert!(is_tflite(&buf));
+        let info = inspect_bytes(&buf).unwrap();
+        assert_eq!(info.format, Format::TFLite);
+        assert_eq!(info.signatures.len(), 1);
+        let sig = &info.signatures[0];
+        assert_eq!(sig.name, "main");
+        assert_eq!(sig.inputs.len(), 1);
+        assert_eq!(sig.inputs[0].name, "input");
+        assert_eq!(sig.inputs[0].dtype, Some(DataType::Int8));
+        assert_eq!(sig.inputs[0].shape, Some(vec![-1, 224, 224, 3]));
+        assert_eq!(
+            sig.inputs[0].quantization,
+            Some(Quantization {
+                scale: vec![0.5],
+                zero_point: vec![-128],
+                dimension: 0,
+            })
+        );
+        // Tensor indices out of range are skipped.
+        assert_eq!(sig.outputs.len(), 1);
+        assert_eq!(sig.outputs[0].dtype, Some(DataType::Float));
+        assert_eq!(sig.outputs[0].shape, Some(vec![1, 10]));
+        assert_eq!(sig.outputs[0].quantization, None);
+        let ops: Vec<_> = info
+            .op_counts
+            .iter()
+            .map(|(k, v)| (k.as_str(), *v))
+            .collect();
+        assert_eq!(ops, [("CONV_2D", 2), ("FULLY_CONNECTED", 1), ("MyOp", 1)]);
+        assert_eq!(
+            info.metadata,
+            [
+                ("schema_version".to_string(), "3".to_string()),
+                ("description".to_string(), "test model".to_string()),
+                ("min_runtime_version".to_string(), "1.5.0".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn signature_defs_replace_subgraphs() {
+        let mut fields = model_fields();
+        let tensor_map = |key, index| vec![vec![(0, Str(key)), (1, U32(index))]];
+        fields.push((
+            7,
+            Tables(vec![vec![
+                (0, Tables(tensor_map("x", 0))),
+                (1, Tables(tensor_map("y", 1))),
+                (2, Str("serving_default")),
+                (4, U32(0)),
+            ]]),
+        ));
+        let info = inspect(&model(fields)).unwrap();
+        assert_eq!(info.signatures.len(), 1);
+        let sig = &info.signatures[0];
+        assert_eq!(sig.name, "serving_default");
+        assert_eq!(sig.inputs[0].key.as_deref(), Some("x"));
+        assert_eq!(sig.inputs[0].name, "input");
+        assert_eq!(sig.outputs[0].key.as_deref(), Some("y"));
+        assert_eq!(sig.outputs[0].name, "output");
+    }
+
+    #[test]
+    fn types_without_a_data_type_keep_their_name() {
+        let tensor = |name, dtype| vec![(0, I32s(vec![2])), (1, U8(dtype)), (3, Str(name))];
+        let fields = vec![(
+            2,
+            Tables(vec![vec![
+                (0, Tables(vec![tensor("a", 17), tensor("b", 40)])),
+                (1, I32s(vec![0, 1])),
+            ]]),
+        )];
+        let info = inspect(&model(fields)).unwrap();
+        let inputs = &info.signatures[0].inputs;
+        assert_eq!(inputs[0].dtype, None);
+        assert_eq!(inputs[0].to_string(), "a: int4 [2]");
+        assert_eq!(inputs[1].dtype, None);
+        assert_eq!(inputs[1].to_string(), "b: type(40) [2]");
+    }
+
+    #[cfg(feature = "wasi-nn")]
+    #[test]
+    fn tensor_names_in_index_order() {
+        let tensor = |name| vec![(0, I32s(vec![1])), (1, U8(0)), (3, Str(name))];
+        let subgraph = |outputs| {
+            vec![(
+                2,
+                Tables(vec![vec![
+                    (0, Tables(vec![tensor("a"), tensor("b"), tensor("c")])),
+                    (1, I32s(vec![2, 0])),
+                    (2, I32s(outputs)),
+                ]]),
+            )]
+        };
+        let names = tensor_names(&model(subgraph(vec![1]))).unwrap();
+        assert_eq!(names, (vec!["c".into(), "a".into()], vec!["b".into()]));
+        // A missing tensor would shift the indices of the next ones.
+        assert!(tensor_names(&model(subgraph(vec![1, 3]))).is_err());
+        assert_eq!(
+            tensor_names(&model(Vec::new())).unwrap(),
+            Default::default()
+        );
+    }
+
+    #[test]
+    fn truncated_and_malformed_input_is_an_error() {
+        let buf = model(model_fields());
+        for len in 0..buf.len() {
+            let _ = inspect_bytes(&buf[..len]);
+        }
+        // The metadata entry is written last.
+        assert!(matches!(
+            inspect(&buf[..buf.len() - 1]),
+            Err(Error::Malformed(_))
+        ));
+        assert!(inspect(&buf[..8]).is_err());
+        // Flipping any byte must not panic.
+        for i in 0..buf.len() {
+            let mut bad = buf.clone();
+            bad[i] ^= 0xff;
+            let _ = inspect(&bad);
+        }
+        let mut bad = buf.clone();
+        bad[0..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(inspect(&bad).is_err());
+    }
+}
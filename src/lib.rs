@@ -10,59 +10,92 @@
 //! use wasmedge_tensorflow_interface;
 //! ```
 
+// The generated bindings cover every host function, including those only used
+// with some features.
+#[allow(dead_code)]
 mod generated_tf;
+#[allow(dead_code)]
 mod generated_tflite;
+#[allow(dead_code)]
 mod generated_img;
-pub use generated_tf::*;
-pub use generated_tflite::*;
-pub use generated_img::*;
+#[cfg(feature = "wasi-nn")]
+#[allow(dead_code)]
+mod generated_nn;
+pub mod postprocess;
+pub mod strings;
+#[cfg(feature = "ndarray")]
+mod array;
+pub mod convert;
+mod types;
+pub mod error;
+mod buffer;
+mod batch;
+mod validate;
+pub mod pool;
+pub mod future;
+#[cfg(feature = "extended-abi")]
+pub mod host;
+pub mod profile;
+pub mod npy;
+pub mod testing;
+pub mod record;
+#[cfg(any(feature = "tfinspect", feature = "wasi-nn"))]
+pub mod inspect;
+#[cfg(feature = "wasi-nn")]
+mod wasi_nn;
+// The raw host bindings of the released plug-ins. The host functions added
+// since, and the names shared by the TensorFlow and TensorFlow-Lite bindings,
+// are only used through the session types.
+pub use generated_tf::{wasmedge_tensorflow, WasmedgeTfErrno, WASMEDGE_TF_ERRNO_SUCCESS, WASMEDGE_TF_ERRNO_INVALID_ARGUMENT,
+    WASMEDGE_TF_ERRNO_INVALID_ENCODING, WASMEDGE_TF_ERRNO_MISSING_MEMORY, WASMEDGE_TF_ERRNO_BUSY,
+    WASMEDGE_TF_ERRNO_RUNTIME_ERROR, MetagraphTag, MetagraphTagList, TensorDimensions, create_session_saved_model,
+    append_output, clear_input, clear_output};
+pub use generated_tflite::{wasmedge_tensorflowlite, WasmedgeTfliteErrno, WASMEDGE_TFLITE_ERRNO_SUCCESS,
+    WASMEDGE_TFLITE_ERRNO_INVALID_ARGUMENT, WASMEDGE_TFLITE_ERRNO_INVALID_ENCODING, WASMEDGE_TFLITE_ERRNO_MISSING_MEMORY,
+    WASMEDGE_TFLITE_ERRNO_BUSY, WASMEDGE_TFLITE_ERRNO_RUNTIME_ERROR};
+pub use generated_img::{wasmedge_image, WasmedgeImageErrno, WASMEDGE_IMAGE_ERRNO_SUCCESS, WASMEDGE_IMAGE_ERRNO_FAIL,
+    WasmedgeImageRawType, WASMEDGE_IMAGE_RAW_TYPE_RGB8, WASMEDGE_IMAGE_RAW_TYPE_BGR8, WASMEDGE_IMAGE_RAW_TYPE_RGB32F,
+    WASMEDGE_IMAGE_RAW_TYPE_BGR32F, ImgBuffer, BufMaxSize, load_jpg, load_png};
+pub use types::{DataType, TensorType};
+pub use error::Error;
+pub use buffer::OutputBuffer;
+pub use batch::BatchBuilder;
+pub use pool::SessionPool;
+pub use validate::InputSpec;
+#[cfg(feature = "wasi-nn")]
+pub use generated_nn::NnErrno;
+#[cfg(feature = "wasi-nn")]
+pub use wasi_nn::TFLiteSession;
+use std::collections::HashMap;
 use std::mem;
 
-// TensorType trait. Internal only.
-pub trait TensorType: Clone {
-    type InnerType;
-    fn val() -> u32;
-    fn zero() -> Self;
-}
-
-// Macro for mapping rust types onto tensor type. Internal only.
-macro_rules! tensor_type {
-    ($rust_type:ty, $type_val:expr, $zero:expr) => {
-        impl TensorType for $rust_type {
-            type InnerType = $rust_type;
-            fn val() -> u32 {
-                $type_val
-            }
-
-            fn zero() -> Self {
-                $zero
-            }
-        }
-    };
-}
-tensor_type!(f32, 1, 0.0f32);
-tensor_type!(f64, 2, 0.0f64);
-tensor_type!(i32, 3, 0);
-tensor_type!(u8, 4, 0);
-tensor_type!(u16, 17, 0);
-tensor_type!(u32, 22, 0);
-tensor_type!(u64, 23, 0);
-tensor_type!(i16, 5, 0);
-tensor_type!(i8, 6, 0);
-tensor_type!(i64, 9, 0);
-tensor_type!(bool, 10, false);
-
 // The TensorFlow session structure.
 pub struct TFSession {
     context: generated_tf::Session,
+    // Expected types and shapes of the inputs, by name.
+    input_specs: HashMap<String, InputSpec>,
 }
 
 impl TFSession {
+    // Create a session from a frozen graph. The buffer is passed to the host
+    // without being copied; an owned `Vec<u8>` is released when this returns.
     pub fn new<S: AsRef<[u8]>>(model_buf: S) -> TFSession {
-        let data = Vec::from(model_buf.as_ref());
         unsafe {
             TFSession {
-                context: generated_tf::create_session(&data).unwrap(),
+                context: generated_tf::create_session(model_buf.as_ref()).unwrap(),
+                input_specs: HashMap::new(),
+            }
+        }
+    }
+
+    // Create a session from a frozen graph file, read by the host from a WASI-preopened
+    // path without passing through the Wasm memory.
+    #[cfg(feature = "extended-abi")]
+    pub fn from_file(model_path: &str) -> TFSession {
+        unsafe {
+            TFSession {
+                context: generated_tf::create_session_from_file(model_path).unwrap(),
+                input_specs: HashMap::new(),
             }
         }
     }
@@ -71,33 +104,109 @@ impl TFSession {
         unsafe {
             TFSession {
                 context: generated_tf::create_session_saved_model(model_path, tags).unwrap(),
+                input_specs: HashMap::new(),
             }
         }
     }
 
     // Add input name, dimension, operation index, and input tensor into context.
+    // Panics if the input does not fit its shape or its `InputSpec`.
     pub fn add_input<T: TensorType>(
         &mut self,
         name: &str,
         tensor_buf: &[T],
         shape: &[u64],
     ) -> &mut TFSession {
+        if let Err(e) = self.try_add_input(name, tensor_buf, shape) {
+            panic!("add_input `{}`: {}", name, e);
+        }
+        self
+    }
+
+    // Add an input tensor after checking that the element count matches the shape,
+    // that the lengths fit the host functions, and the `InputSpec` of the input if
+    // one is set.
+    pub fn try_add_input<T: TensorType>(
+        &mut self,
+        name: &str,
+        tensor_buf: &[T],
+        shape: &[u64],
+    ) -> error::Result<&mut TFSession> {
+        let raw_buf = T::as_bytes(tensor_buf);
+        validate::check_input(name, tensor_buf.len(), raw_buf.len(), shape)?;
+        if let Some(spec) = self.input_specs.get(name) {
+            spec.check(T::DATA_TYPE, shape)?;
+        }
         unsafe {
-            let raw_buf = std::slice::from_raw_parts(
-                (tensor_buf as *const [T]).cast(),
-                tensor_buf.len() * mem::size_of::<T::InnerType>()
-            );
             generated_tf::append_input(
                 self.context,
                 name,
                 shape,
-                T::val(),
+                T::DATA_TYPE.value(),
                 raw_buf,
-            ).unwrap();
+            )?;
         }
+        Ok(self)
+    }
+
+    // Set the expected type and shape of an input, which `add_input` checks
+    // before passing the input to the host. Specs are never read from the model:
+    // inputs are only checked against the specs set here.
+    pub fn set_input_spec(&mut self, name: &str, spec: InputSpec) -> &mut TFSession {
+        self.input_specs.insert(name.to_string(), spec);
         self
     }
 
+    // Add a string input tensor into context.
+    #[cfg(feature = "extended-abi")]
+    pub fn add_input_strings<S: AsRef<str>>(
+        &mut self,
+        name: &str,
+        strings: &[S],
+        shape: &[u64],
+    ) -> &mut TFSession {
+        let raw_buf = strings::encode_strings(strings);
+        if let Err(e) = self.append_string_input(name, strings.len(), &raw_buf, shape) {
+            panic!("add_input_strings `{}`: {}", name, e);
+        }
+        self
+    }
+
+    // Add a string input tensor of binary elements, such as serialized protos,
+    // into context.
+    #[cfg(feature = "extended-abi")]
+    pub fn add_input_bytes<B: AsRef<[u8]>>(
+        &mut self,
+        name: &str,
+        elements: &[B],
+        shape: &[u64],
+    ) -> &mut TFSession {
+        let raw_buf = strings::encode_bytes(elements);
+        if let Err(e) = self.append_string_input(name, elements.len(), &raw_buf, shape) {
+            panic!("add_input_bytes `{}`: {}", name, e);
+        }
+        self
+    }
+
+    // Check and append a string input of `len` elements encoded in `raw_buf`.
+    #[cfg(feature = "extended-abi")]
+    fn append_string_input(
+        &mut self,
+        name: &str,
+        len: usize,
+        raw_buf: &[u8],
+        shape: &[u64],
+    ) -> error::Result<()> {
+        validate::check_input(name, len, raw_buf.len(), shape)?;
+        if let Some(spec) = self.input_specs.get(name) {
+            spec.check(DataType::String, shape)?;
+        }
+        unsafe {
+            generated_tf::append_string_input(self.context, name, shape, raw_buf)?;
+        }
+        Ok(())
+    }
+
     // Add output name and operation index into context.
     pub fn add_output(&mut self, name: &str) -> &mut TFSession {
         unsafe {
@@ -133,18 +242,113 @@ impl TFSession {
     // Get output tensor data by name.
     pub fn get_output<T: TensorType>(&self, name: &str) -> Vec<T> {
         // Get tensor data.
-        let mut data: Vec<T> = Vec::new();
         unsafe {
             let tensor = generated_tf::get_output_tensor(self.context, name).unwrap();
             let buf_len = generated_tf::get_tensor_len(self.context, tensor).unwrap() as usize;
-            if buf_len == 0 {
-                return data;
+            types::read_tensor(buf_len, |buf, buf_max_size| {
+                generated_tf::get_tensor_data(self.context, tensor, buf, buf_max_size).unwrap();
+            })
+        }
+    }
+
+    // Get the element count of an output tensor, to size the buffer of `get_output_into`.
+    pub fn get_output_len<T: TensorType>(&self, name: &str) -> usize {
+        self.output_tensor::<T>(name).unwrap().1
+    }
+
+    // Get output tensor data by name into a caller-provided buffer.
+    // Returns the number of elements written at the front of the buffer.
+    pub fn get_output_into<T: TensorType>(
+        &self,
+        name: &str,
+        buf: &mut [T],
+    ) -> error::Result<usize> {
+        let (tensor, len) = self.output_tensor::<T>(name)?;
+        self.read_output_into(tensor, len, buf)
+    }
+
+    // Get output tensor data by name into a reusable buffer, which only reallocates
+    // when the output grows beyond its capacity.
+    pub fn get_output_buffered<'a, T: TensorType>(
+        &self,
+        name: &str,
+        buf: &'a mut OutputBuffer<T>,
+    ) -> error::Result<&'a [T]> {
+        let (tensor, len) = self.output_tensor::<T>(name)?;
+        let data = buf.resize(len);
+        self.read_output_into(tensor, len, data)?;
+        Ok(data)
+    }
+
+    // Look up an output tensor by name, with its element count.
+    fn output_tensor<T: TensorType>(&self, name: &str) -> error::Result<(generated_tf::Tensor, usize)> {
+        unsafe {
+            let tensor = generated_tf::get_output_tensor(self.context, name)?;
+            let len = generated_tf::get_tensor_len(self.context, tensor)? as usize / mem::size_of::<T>();
+            Ok((tensor, len))
+        }
+    }
+
+    // Read the `len` elements of an output tensor into the front of `buf`.
+    fn read_output_into<T: TensorType>(
+        &self,
+        tensor: generated_tf::Tensor,
+        len: usize,
+        buf: &mut [T],
+    ) -> error::Result<usize> {
+        types::read_output_into(buf, len, |buf, buf_max_size| unsafe {
+            generated_tf::get_tensor_data(self.context, tensor, buf, buf_max_size).map(|_| ())
+        })
+    }
+
+    // Get string output tensor data by name. Invalid UTF-8 is replaced, so use
+    // `get_output_bytes` for binary elements.
+    #[cfg(feature = "extended-abi")]
+    pub fn get_output_strings(&self, name: &str) -> Vec<String> {
+        strings::decode_strings(&self.get_string_output(name))
+    }
+
+    // Get string output tensor data by name as the raw bytes of each element.
+    #[cfg(feature = "extended-abi")]
+    pub fn get_output_bytes(&self, name: &str) -> Vec<Vec<u8>> {
+        strings::decode_bytes(&self.get_string_output(name))
+    }
+
+    // Get the encoded elements of a string output tensor.
+    #[cfg(feature = "extended-abi")]
+    fn get_string_output(&self, name: &str) -> Vec<u8> {
+        unsafe {
+            let tensor = generated_tf::get_output_tensor(self.context, name).unwrap();
+            let buf_len = generated_tf::get_string_tensor_len(self.context, tensor).unwrap() as usize;
+            types::read_tensor(buf_len, |buf, buf_max_size| {
+                generated_tf::get_string_tensor_data(self.context, tensor, buf, buf_max_size).unwrap();
+            })
+        }
+    }
+
+    // Get output tensor dimensions by name.
+    #[cfg(feature = "extended-abi")]
+    pub fn get_output_shape(&self, name: &str) -> error::Result<Vec<u64>> {
+        unsafe {
+            let tensor = generated_tf::get_output_tensor(self.context, name)?;
+            let dims_len = generated_tf::get_tensor_dims_len(self.context, tensor)?;
+            let mut dims: Vec<u64> = vec![0; dims_len as usize];
+            let written = generated_tf::get_tensor_dims(self.context, tensor, dims.as_mut_ptr(), dims_len)?;
+            if written != dims_len {
+                return Err(Error::LengthMismatch {
+                    expected: dims_len as usize,
+                    actual: written as usize,
+                });
             }
-            data.resize(buf_len / mem::size_of::<T::InnerType>(), T::zero());
-            generated_tf::get_tensor_data(self.context, tensor, data.as_mut_ptr() as *mut u8, buf_len as u32).unwrap();
-            return data;
+            Ok(dims)
         }
     }
+
+    // Output shapes need the `get_tensor_dims` host functions of the `extended-abi` feature.
+    #[cfg(not(feature = "extended-abi"))]
+    pub fn get_output_shape(&self, _name: &str) -> error::Result<Vec<u64>> {
+        Err(Error::Unsupported("output shapes need the `extended-abi` feature"))
+    }
 }
 
 impl Drop for TFSession {
@@ -156,37 +360,79 @@ impl Drop for TFSession {
 }
 
 // The TensorFlow-Lite session structure.
+#[cfg(not(feature = "wasi-nn"))]
 pub struct TFLiteSession {
     context: generated_tflite::Session,
+    // Expected types and shapes of the inputs, by name.
+    input_specs: HashMap<String, InputSpec>,
 }
 
+#[cfg(not(feature = "wasi-nn"))]
 impl TFLiteSession {
+    // Create a session from a TensorFlow-Lite model. The buffer is passed to the
+    // host without being copied; an owned `Vec<u8>` is released when this returns.
     pub fn new<S: AsRef<[u8]>>(model_buf: S) -> TFLiteSession {
-        let data = Vec::from(model_buf.as_ref());
         unsafe {
             TFLiteSession {
-                context: generated_tflite::create_session(&data).unwrap(),
+                context: generated_tflite::create_session(model_buf.as_ref()).unwrap(),
+                input_specs: HashMap::new(),
+            }
+        }
+    }
+
+    // Create a session from a TensorFlow-Lite model file, read by the host from a WASI-preopened
+    // path without passing through the Wasm memory.
+    #[cfg(feature = "extended-abi")]
+    pub fn from_file(model_path: &str) -> TFLiteSession {
+        unsafe {
+            TFLiteSession {
+                context: generated_tflite::create_session_from_file(model_path).unwrap(),
+                input_specs: HashMap::new(),
             }
         }
     }
 
     // Add input name, dimension, operation index, and input tensor into context.
+    // Panics if the input does not fit the host functions or its `InputSpec`.
     pub fn add_input<T: TensorType>(
         &mut self,
         name: &str,
         tensor_buf: &[T],
     ) -> &mut TFLiteSession {
+        if let Err(e) = self.try_add_input(name, tensor_buf) {
+            panic!("add_input `{}`: {}", name, e);
+        }
+        self
+    }
+
+    // Add an input tensor after checking that the lengths fit the host functions,
+    // and the `InputSpec` of the input if one is set.
+    pub fn try_add_input<T: TensorType>(
+        &mut self,
+        name: &str,
+        tensor_buf: &[T],
+    ) -> error::Result<&mut TFLiteSession> {
+        let raw_buf = T::as_bytes(tensor_buf);
+        let len = tensor_buf.len();
+        validate::check_input(name, len, raw_buf.len(), &[len as u64])?;
+        if let Some(spec) = self.input_specs.get(name) {
+            spec.check_len(T::DATA_TYPE, len)?;
+        }
         unsafe {
-            let raw_buf = std::slice::from_raw_parts(
-                (tensor_buf as *const [T]).cast(),
-                tensor_buf.len() * mem::size_of::<T::InnerType>()
-            );
             generated_tflite::append_input(
                 self.context,
                 name,
                 raw_buf,
-            ).unwrap();
+            )?;
         }
+        Ok(self)
+    }
+
+    // Set the expected type and shape of an input, which `add_input` checks
+    // before passing the input to the host. Specs are never read from the model:
+    // inputs are only checked against the specs set here.
+    pub fn set_input_spec(&mut self, name: &str, spec: InputSpec) -> &mut TFLiteSession {
+        self.input_specs.insert(name.to_string(), spec);
         self
     }
 
@@ -201,20 +447,91 @@ impl TFLiteSession {
     // Get output tensor data by name.
     pub fn get_output<T: TensorType>(&self, name: &str) -> Vec<T> {
         // Get tensor data.
-        let mut data: Vec<T> = Vec::new();
         unsafe {
             let tensor = generated_tflite::get_output_tensor(self.context, name).unwrap();
             let buf_len = generated_tflite::get_tensor_len(self.context, tensor).unwrap() as usize;
-            if buf_len == 0 {
-                return data;
+            types::read_tensor(buf_len, |buf, buf_max_size| {
+                generated_tflite::get_tensor_data(self.context, tensor, buf, buf_max_size).unwrap();
+            })
+        }
+    }
+
+    // Get the element count of an output tensor, to size the buffer of `get_output_into`.
+    pub fn get_output_len<T: TensorType>(&self, name: &str) -> usize {
+        self.output_tensor::<T>(name).unwrap().1
+    }
+
+    // Get output tensor data by name into a caller-provided buffer.
+    // Returns the number of elements written at the front of the buffer.
+    pub fn get_output_into<T: TensorType>(
+        &self,
+        name: &str,
+        buf: &mut [T],
+    ) -> error::Result<usize> {
+        let (tensor, len) = self.output_tensor::<T>(name)?;
+        self.read_output_into(tensor, len, buf)
+    }
+
+    // Get output tensor data by name into a reusable buffer, which only reallocates
+    // when the output grows beyond its capacity.
+    pub fn get_output_buffered<'a, T: TensorType>(
+        &self,
+        name: &str,
+        buf: &'a mut OutputBuffer<T>,
+    ) -> error::Result<&'a [T]> {
+        let (tensor, len) = self.output_tensor::<T>(name)?;
+        let data = buf.resize(len);
+        self.read_output_into(tensor, len, data)?;
+        Ok(data)
+    }
+
+    // Look up an output tensor by name, with its element count.
+    fn output_tensor<T: TensorType>(&self, name: &str) -> error::Result<(generated_tflite::Tensor, usize)> {
+        unsafe {
+            let tensor = generated_tflite::get_output_tensor(self.context, name)?;
+            let len = generated_tflite::get_tensor_len(self.context, tensor)? as usize / mem::size_of::<T>();
+            Ok((tensor, len))
+        }
+    }
+
+    // Read the `len` elements of an output tensor into the front of `buf`.
+    fn read_output_into<T: TensorType>(
+        &self,
+        tensor: generated_tflite::Tensor,
+        len: usize,
+        buf: &mut [T],
+    ) -> error::Result<usize> {
+        types::read_output_into(buf, len, |buf, buf_max_size| unsafe {
+            generated_tflite::get_tensor_data(self.context, tensor, buf, buf_max_size).map(|_| ())
+        })
+    }
+
+    // Get output tensor dimensions by name.
+    #[cfg(feature = "extended-abi")]
+    pub fn get_output_shape(&self, name: &str) -> error::Result<Vec<u64>> {
+        unsafe {
+            let tensor = generated_tflite::get_output_tensor(self.context, name)?;
+            let dims_len = generated_tflite::get_tensor_dims_len(self.context, tensor)?;
+            let mut dims: Vec<u64> = vec![0; dims_len as usize];
+            let written = generated_tflite::get_tensor_dims(self.context, tensor, dims.as_mut_ptr(), dims_len)?;
+            if written != dims_len {
+                return Err(Error::LengthMismatch {
+                    expected: dims_len as usize,
+                    actual: written as usize,
+                });
             }
-            data.resize(buf_len / mem::size_of::<T::InnerType>(), T::zero());
-            generated_tflite::get_tensor_data(self.context, tensor, data.as_mut_ptr() as *mut u8, buf_len as u32).unwrap();
-            return data;
+            Ok(dims)
         }
     }
+
+    // Output shapes need the `get_tensor_dims` host functions of the `extended-abi` feature.
+    #[cfg(not(feature = "extended-abi"))]
+    pub fn get_output_shape(&self, _name: &str) -> error::Result<Vec<u64>> {
+        Err(Error::Unsupported("output shapes need the `extended-abi` feature"))
+    }
 }
 
+#[cfg(not(feature = "wasi-nn"))]
 impl Drop for TFLiteSession {
     fn drop(&mut self) {
         unsafe {
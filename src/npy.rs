@@ -0,0 +1,819 @@
+//! NumPy `.npy` and `.npz` files.
+//!
+//! Reads arrays saved with `numpy.save` and `numpy.savez` into the element
+//! vectors and shapes taken by `add_input`, and writes `get_output` results back
+//! to `.npy` files, so that outputs can be compared with the ones computed in
+//! Python. Big-endian and Fortran-ordered arrays are converted when they are
+//! read. Archives written by `numpy.savez_compressed` need the `miniz_oxide`
+//! feature.
+//!
+//! ```rust, ignore
+//! let input = npy::read("input.npy").unwrap();
+//! session.add_input("input", &input.to_vec::<f32>().unwrap(), input.shape()).run();
+//! let output: Vec<f32> = session.get_output("output");
+//! npy::write("output.npy", &output, &session.get_output_shape("output").unwrap()).unwrap();
+//! ```
+
+use crate::{DataType, TensorType};
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    // The file is not a valid `.npy` or `.npz` file.
+    Malformed(&'static str),
+    // A dtype without tensor type, a tensor type without dtype, or an archive
+    // compression method that is not supported.
+    Unsupported(String),
+    // `to_vec` was called with another element type than the one of the array.
+    // As with `error::Error::TypeMismatch`, `expected` is the element type of the
+    // array and `actual` the one of the caller.
+    TypeMismatch {
+        expected: DataType,
+        actual: DataType,
+    },
+    // The element count does not match the shape.
+    ShapeMismatch {
+        shape: Vec<u64>,
+        len: usize,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Malformed(msg) => write!(f, "malformed file: {}", msg),
+            Error::Unsupported(what) => write!(f, "unsupported {}", what),
+            Error::TypeMismatch { expected, actual } => write!(
+                f,
+                "type mismatch: {} expected, {} provided",
+                expected, actual
+            ),
+            Error::ShapeMismatch { shape, len } => {
+                write!(f, "shape mismatch: {:?} with {} elements", shape, len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+// The NumPy type code of a tensor type, without byte order.
+fn type_code(dtype: DataType) -> Option<&'static str> {
+    Some(match dtype {
+        DataType::Float => "f4",
+        DataType::Double => "f8",
+        DataType::Half => "f2",
+        DataType::Int8 => "i1",
+        DataType::Int16 => "i2",
+        DataType::Int32 => "i4",
+        DataType::Int64 => "i8",
+        DataType::UInt8 => "u1",
+        DataType::UInt16 => "u2",
+        DataType::UInt32 => "u4",
+        DataType::UInt64 => "u8",
+        DataType::Bool => "b1",
+        DataType::Complex64 => "c8",
+        DataType::Complex128 => "c16",
+        _ => return None,
+    })
+}
+
+// The tensor type of a NumPy type code, without byte order.
+fn data_type(code: &str) -> Option<DataType> {
+    [
+        DataType::Float,
+        DataType::Double,
+        DataType::Half,
+        DataType::Int8,
+        DataType::Int16,
+        DataType::Int32,
+        DataType::Int64,
+        DataType::UInt8,
+        DataType::UInt16,
+        DataType::UInt32,
+        DataType::UInt64,
+        DataType::Bool,
+        DataType::Complex64,
+        DataType::Complex128,
+    ]
+    .into_iter()
+    .find(|t| type_code(*t) == Some(code))
+}
+
+// A dense array in C order, with little-endian elements.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Array {
+    dtype: DataType,
+    shape: Vec<u64>,
+    data: Vec<u8>,
+}
+
+// Number of elements of an array of the given shape, 1 for a scalar.
+fn element_count(shape: &[u64]) -> Option<usize> {
+    shape
+        .iter()
+        .try_fold(1usize, |n, d| n.checked_mul(usize::try_from(*d).ok()?))
+}
+
+impl Array {
+    // Create an array from the elements of a tensor in C order.
+    pub fn new<T: TensorType>(data: &[T], shape: &[u64]) -> Result<Array> {
+        if element_count(shape) != Some(data.len()) {
+            return Err(Error::ShapeMismatch {
+                shape: shape.to_vec(),
+                len: data.len(),
+            });
+        }
+        if type_code(T::DATA_TYPE).is_none() {
+            return Err(Error::Unsupported(format!("tensor type {}", T::DATA_TYPE)));
+        }
+        Ok(Array {
+            dtype: T::DATA_TYPE,
+            shape: shape.to_vec(),
+            data: T::as_bytes(data).to_vec(),
+        })
+    }
+
+    pub fn data_type(&self) -> DataType {
+        self.dtype
+    }
+
+    pub fn shape(&self) -> &[u64] {
+        &self.shape
+    }
+
+    // Number of elements.
+    pub fn len(&self) -> usize {
+        element_count(&self.shape).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // The element bytes, in C order.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    // Copy the elements out as `T`, which must be the element type of the array.
+    pub fn to_vec<T: TensorType>(&self) -> Result<Vec<T>> {
+        if T::DATA_TYPE != self.dtype {
+            return Err(Error::TypeMismatch {
+                expected: self.dtype,
+                actual: T::DATA_TYPE,
+            });
+        }
+        Ok(T::from_bytes(&self.data))
+    }
+
+    // Parse the content of a `.npy` file.
+    pub fn from_bytes(buf: &[u8]) -> Result<Array> {
+        if buf.len() < 10 || !buf.starts_with(MAGIC) {
+            return Err(Error::Malformed("missing .npy magic string"));
+        }
+        let (header_len, start): (usize, usize) = match buf[6] {
+            1 => (u16::from_le_bytes([buf[8], buf[9]]) as usize, 10),
+            2 | 3 if buf.len() >= 12 => (
+                u32::from_le_bytes([buf[8], buf[9], buf[10], buf[11]]) as usize,
+                12,
+            ),
+            2 | 3 => return Err(Error::Malformed("truncated .npy header")),
+            v => return Err(Error::Unsupported(format!(".npy format version {}", v))),
+        };
+        let data_start = start
+            .checked_add(header_len)
+            .ok_or(Error::Malformed("truncated .npy header"))?;
+        let header = buf
+            .get(start..data_start)
+            .ok_or(Error::Malformed("truncated .npy header"))?;
+        let header = std::str::from_utf8(header)
+            .map_err(|_| Error::Malformed("invalid .npy header encoding"))?;
+        let header = Header::parse(header)?;
+
+        let (order, code) = match header.descr.chars().next() {
+            Some(c @ ('<' | '>' | '|' | '=')) => (c, &header.descr[1..]),
+            _ => ('=', header.descr.as_str()),
+        };
+        let dtype = data_type(code)
+            .ok_or_else(|| Error::Unsupported(format!("dtype `{}`", header.descr)))?;
+        let size = dtype.size_of().unwrap();
+        let len = element_count(&header.shape).ok_or(Error::Malformed("array too large"))?;
+        let data = len
+            .checked_mul(size)
+            .and_then(|n| data_start.checked_add(n))
+            .and_then(|end| buf.get(data_start..end))
+            .ok_or(Error::Malformed("truncated .npy data"))?;
+
+        let mut data = data.to_vec();
+        if order == '>' {
+            // Complex numbers are pairs of big-endian floats.
+            let part = match dtype {
+                DataType::Complex64 | DataType::Complex128 => size / 2,
+                _ => size,
+            };
+            data.chunks_exact_mut(part).for_each(<[u8]>::reverse);
+        }
+        if header.fortran_order && header.shape.len() > 1 {
+            data = fortran_to_c(&data, &header.shape, size);
+        }
+        Ok(Array {
+            dtype,
+            shape: header.shape,
+            data,
+        })
+    }
+
+    // The content of a version 1.0 `.npy` file, or 2.0 when the header needs it.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let code = type_code(self.dtype).unwrap();
+        let order = if self.dtype.size_of() == Some(1) {
+            '|'
+        } else {
+            '<'
+        };
+        let dims: Vec<String> = self.shape.iter().map(|d| d.to_string()).collect();
+        let shape = match dims.len() {
+            1 => format!("({},)", dims[0]),
+            _ => format!("({})", dims.join(", ")),
+        };
+        let mut header = format!(
+            "{{'descr': '{}{}', 'fortran_order': False, 'shape': {}, }}",
+            order, code, shape
+        );
+        // The data starts at a multiple of 64 bytes after the magic string,
+        // version, header length and the newline ending the header.
+        let prefix = if header.len() + 11 > u16::MAX as usize {
+            12
+        } else {
+            10
+        };
+        while (prefix + header.len() + 1) % 64 != 0 {
+            header.push(' ');
+        }
+        header.push('\n');
+
+        let mut buf = MAGIC.to_vec();
+        if prefix == 10 {
+            buf.extend_from_slice(&[1, 0]);
+            buf.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        } else {
+            buf.extend_from_slice(&[2, 0]);
+            buf.extend_from_slice(&(header.len() as u32).to_le_bytes());
+        }
+        buf.extend_from_slice(header.as_bytes());
+        buf.extend_from_slice(&self.data);
+        buf
+    }
+}
+
+// The fields of a `.npy` header, a Python dict literal such as
+// `{'descr': '<f4', 'fortran_order': False, 'shape': (1, 224, 224, 3), }`.
+struct Header {
+    descr: String,
+    fortran_order: bool,
+    shape: Vec<u64>,
+}
+
+impl Header {
+    fn parse(text: &str) -> Result<Header> {
+        // The text following the `key` entry of the dict.
+        let value = |key: &str| -> Result<&str> {
+            let pos = text
+                .find(&format!("'{}'", key))
+                .or_else(|| text.find(&format!("\"{}\"", key)))
+                .ok_or(Error::Malformed("incomplete .npy header"))?;
+            Ok(text[pos + key.len() + 2..]
+                .trim_start()
+                .trim_start_matches(':')
+                .trim_start())
+        };
+
+        let descr = value("descr")?;
+        let quote = descr
+            .chars()
+            .next()
+            .filter(|c| *c == '\'' || *c == '"')
+            .ok_or_else(|| Error::Unsupported("structured dtype".to_string()))?;
+        let descr = descr[1..]
+            .split(quote)
+            .next()
+            .ok_or(Error::Malformed("invalid .npy dtype"))?;
+
+        let fortran_order = value("fortran_order")?.starts_with("True");
+
+        let shape = value("shape")?
+            .strip_prefix('(')
+            .and_then(|s| s.split(')').next())
+            .ok_or(Error::Malformed("invalid .npy shape"))?;
+        let shape = shape
+            .split(',')
+            .map(|d| d.trim().trim_end_matches('L'))
+            .filter(|d| !d.is_empty())
+            .map(|d| {
+                d.parse()
+                    .map_err(|_| Error::Malformed("invalid .npy shape"))
+            })
+            .collect::<Result<Vec<u64>>>()?;
+
+        Ok(Header {
+            descr: descr.to_string(),
+            fortran_order,
+            shape,
+        })
+    }
+}
+
+// Reorder the elements of a Fortran-ordered array into C order.
+fn fortran_to_c(data: &[u8], shape: &[u64], size: usize) -> Vec<u8> {
+    let dims: Vec<usize> = shape.iter().map(|d| *d as usize).collect();
+    // Fortran strides, in elements: the first index varies fastest.
+    let mut strides = vec![1; dims.len()];
+    for i in 1..dims.len() {
+        strides[i] = strides[i - 1] * dims[i - 1];
+    }
+    let mut out = Vec::with_capacity(data.len());
+    let mut index = vec![0; dims.len()];
+    for _ in 0..data.len() / size {
+        let offset: usize = index.iter().zip(&strides).map(|(i, s)| i * s).sum();
+        out.extend_from_slice(&data[offset * size..(offset + 1) * size]);
+        // Advance the C-order index, the last dimension fastest.
+        for d in (0..dims.len()).rev() {
+            index[d] += 1;
+            if index[d] < dims[d] {
+                break;
+            }
+            index[d] = 0;
+        }
+    }
+    out
+}
+
+// Read a `.npy` file.
+pub fn read<P: AsRef<Path>>(path: P) -> Result<Array> {
+    Array::from_bytes(&std::fs::read(path)?)
+}
+
+// Write the elements of a tensor in C order, such as a `get_output` result with
+// its `get_output_shape`, to a `.npy` file.
+pub fn write<P: AsRef<Path>, T: TensorType>(path: P, data: &[T], shape: &[u64]) -> Result<()> {
+    std::fs::write(path, Array::new(data, shape)?.to_bytes())?;
+    Ok(())
+}
+
+// Read the arrays of a `.npz` archive, named as the keywords of `numpy.savez`.
+pub fn read_npz<P: AsRef<Path>>(path: P) -> Result<Vec<(String, Array)>> {
+    npz_from_bytes(&std::fs::read(path)?)
+}
+
+// Write arrays to a `.npz` archive, which `numpy.load` reads as a dict.
+pub fn write_npz<P: AsRef<Path>>(path: P, arrays: &[(&str, &Array)]) -> Result<()> {
+    std::fs::write(path, npz_to_bytes(arrays)?)?;
+    Ok(())
+}
+
+const LOCAL_HEADER: u32 = 0x04034b50;
+const CENTRAL_HEADER: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x06054b50;
+const ZIP64_END_LOCATOR: u32 = 0x07064b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY: u32 = 0x06064b50;
+const ZIP64_EXTRA: u16 = 0x0001;
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xedb88320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+};
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, b| {
+        CRC32_TABLE[((crc ^ *b as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn read_u16(buf: &[u8], pos: usize) -> Result<u16> {
+    pos.checked_add(2)
+        .and_then(|end| buf.get(pos..end))
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or(Error::Malformed("truncated zip archive"))
+}
+
+fn read_u32(buf: &[u8], pos: usize) -> Result<u32> {
+    pos.checked_add(4)
+        .and_then(|end| buf.get(pos..end))
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+        .ok_or(Error::Malformed("truncated zip archive"))
+}
+
+fn read_u64(buf: &[u8], pos: usize) -> Result<u64> {
+    pos.checked_add(8)
+        .and_then(|end| buf.get(pos..end))
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .ok_or(Error::Malformed("truncated zip archive"))
+}
+
+fn to_usize(value: u64) -> Result<usize> {
+    usize::try_from(value).map_err(|_| Error::Malformed("zip archive too large"))
+}
+
+// Position and entry count of the central directory of a zip archive.
+fn central_directory(buf: &[u8]) -> Result<(usize, usize)> {
+    // The end record is last, followed by a comment of at most 64 KiB.
+    let end = (0..=buf.len().saturating_sub(22))
+        .rev()
+        .take(u16::MAX as usize + 1)
+        .find(|pos| read_u32(buf, *pos).ok() == Some(END_OF_CENTRAL_DIRECTORY))
+        .ok_or(Error::Malformed("not a zip archive"))?;
+    let entries = read_u16(buf, end + 10)?;
+    let offset = read_u32(buf, end + 16)?;
+    if entries != u16::MAX && offset != u32::MAX {
+        return Ok((offset as usize, entries as usize));
+    }
+    // Zip64 archives, as written by `numpy.savez`, locate their zip64 end record
+    // right before the end record.
+    let locator = end
+        .checked_sub(20)
+        .filter(|pos| read_u32(buf, *pos).ok() == Some(ZIP64_END_LOCATOR))
+        .ok_or(Error::Malformed("missing zip64 end locator"))?;
+    let end64 = to_usize(read_u64(buf, locator + 8)?)?;
+    if read_u32(buf, end64)? != ZIP64_END_OF_CENTRAL_DIRECTORY {
+        return Err(Error::Malformed("missing zip64 end record"));
+    }
+    Ok((
+        to_usize(read_u64(buf, end64 + 48)?)?,
+        to_usize(read_u64(buf, end64 + 32)?)?,
+    ))
+}
+
+// Parse the content of a `.npz` archive.
+pub fn npz_from_bytes(buf: &[u8]) -> Result<Vec<(String, Array)>> {
+    let (mut pos, entries) = central_directory(buf)?;
+    let mut arrays = Vec::with_capacity(entries.min(1024));
+    for _ in 0..entries {
+        if read_u32(buf, pos)? != CENTRAL_HEADER {
+            return Err(Error::Malformed("invalid zip central directory"));
+        }
+        let method = read_u16(buf, pos + 10)?;
+        let crc = read_u32(buf, pos + 16)?;
+        let mut compressed_size = read_u32(buf, pos + 20)? as u64;
+        let mut size = read_u32(buf, pos + 24)? as u64;
+        let name_len = read_u16(buf, pos + 28)? as usize;
+        let extra_len = read_u16(buf, pos + 30)? as usize;
+        let comment_len = read_u16(buf, pos + 32)? as usize;
+        let mut offset = read_u32(buf, pos + 42)? as u64;
+        let name = buf
+            .get(pos + 46..pos + 46 + name_len)
+            .ok_or(Error::Malformed("truncated zip archive"))?;
+        let name = String::from_utf8_lossy(name).into_owned();
+
+        // Sizes and offsets that do not fit 32 bits are in the zip64 extra field.
+        let mut extra = pos + 46 + name_len;
+        let extra_end = extra + extra_len;
+        while extra + 4 <= extra_end {
+            let id = read_u16(buf, extra)?;
+            let len = read_u16(buf, extra + 2)? as usize;
+            if id == ZIP64_EXTRA {
+                let mut field = extra + 4;
+                for value in [&mut size, &mut compressed_size, &mut offset] {
+                    if *value == u32::MAX as u64 {
+                        *value = read_u64(buf, field)?;
+                        field += 8;
+                    }
+                }
+            }
+            extra += 4 + len;
+        }
+        pos = extra_end + comment_len;
+
+        let offset = to_usize(offset)?;
+        if read_u32(buf, offset)? != LOCAL_HEADER {
+            return Err(Error::Malformed("invalid zip local header"));
+        }
+        let start = offset
+            + 30
+            + read_u16(buf, offset + 26)? as usize
+            + read_u16(buf, offset + 28)? as usize;
+        let compressed = start
+            .checked_add(to_usize(compressed_size)?)
+            .and_then(|end| buf.get(start..end))
+            .ok_or(Error::Malformed("truncated zip archive"))?;
+        let data = match method {
+            0 => compressed.to_vec(),
+            #[cfg(feature = "miniz_oxide")]
+            8 => miniz_oxide::inflate::decompress_to_vec(compressed)
+                .map_err(|_| Error::Malformed("invalid deflate stream"))?,
+            #[cfg(not(feature = "miniz_oxide"))]
+            8 => {
+                return Err(Error::Unsupported(
+                    "compressed .npz archive (enable the `miniz_oxide` feature)".to_string(),
+                ))
+            }
+            m => return Err(Error::Unsupported(format!("zip compression method {}", m))),
+        };
+        if data.len() as u64 != size || crc32(&data) != crc {
+            return Err(Error::Malformed("corrupted zip entry"));
+        }
+        let name = name.strip_suffix(".npy").unwrap_or(&name).to_string();
+        arrays.push((name, Array::from_bytes(&data)?));
+    }
+    Ok(arrays)
+}
+
+// The content of an uncompressed `.npz` archive, limited to 4 GiB and 65535
+// arrays.
+pub fn npz_to_bytes(arrays: &[(&str, &Array)]) -> Result<Vec<u8>> {
+    let invalid = |msg| Error::Io(io::Error::new(io::ErrorKind::InvalidInput, msg));
+    let too_large = || invalid(".npz archive larger than 4 GiB");
+    let mut buf = Vec::new();
+    let mut directory = Vec::new();
+    for (name, array) in arrays {
+        let name = format!("{}.npy", name);
+        let data = array.to_bytes();
+        let offset = u32::try_from(buf.len()).map_err(|_| too_large())?;
+        let size = u32::try_from(data.len()).map_err(|_| too_large())?;
+        let crc = crc32(&data);
+
+        // Version needed 2.0, no flags, stored, 1980-01-01 00:00.
+        let mut header = Vec::with_capacity(26);
+        header.extend_from_slice(&20u16.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(&0x21u16.to_le_bytes());
+        header.extend_from_slice(&crc.to_le_bytes());
+        header.extend_from_slice(&size.to_le_bytes());
+        header.extend_from_slice(&size.to_le_bytes());
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+
+        buf.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+        buf.extend_from_slice(&header);
+        buf.extend_from_slice(name.as_bytes());
+        buf.extend_from_slice(&data);
+
+        directory.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+        directory.extend_from_slice(&20u16.to_le_bytes());
+        directory.extend_from_slice(&header);
+        // No comment, disk 0, no attributes.
+        directory.extend_from_slice(&[0; 10]);
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+    let offset = u32::try_from(buf.len()).map_err(|_| too_large())?;
+    let entries = u16::try_from(arrays.len()).map_err(|_| invalid("more than 65535 arrays"))?;
+    buf.extend_from_slice(&directory);
+    buf.extend_from_slice(&END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+    buf.extend_from_slice(&[0; 4]);
+    buf.extend_from_slice(&entries.to_le_bytes());
+    buf.extend_from_slice(&entries.to_le_bytes());
+    buf.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    buf.extend_from_slice(&offset.to_le_bytes());
+    buf.extend_from_slice(&0u16.to_le_bytes());
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error;
+    use crate::validate::InputSpec;
+
+    fn round_trip<T: TensorType + fmt::Debug + PartialEq>(values: &[T]) {
+        let array = Array::new(values, &[values.len() as u64]).unwrap();
+        let bytes = array.to_bytes();
+        // The data is aligned to 64 bytes.
+        assert_eq!((bytes.len() - std::mem::size_of_val(values)) % 64, 0);
+        let read = Array::from_bytes(&bytes).unwrap();
+        assert_eq!(read, array);
+        assert_eq!(read.data_type(), T::DATA_TYPE);
+        assert_eq!(read.to_vec::<T>().unwrap(), values);
+    }
+
+    #[test]
+    fn every_dtype_round_trips() {
+        round_trip(&[1.5f32, -0.0, f32::MAX]);
+        round_trip(&[1.5f64, f64::MIN_POSITIVE]);
+        round_trip(&[i8::MIN, 0, i8::MAX]);
+        round_trip(&[i16::MIN, 0, i16::MAX]);
+        round_trip(&[i32::MIN, 0, i32::MAX]);
+        round_trip(&[i64::MIN, 0, i64::MAX]);
+        round_trip(&[0u8, 255]);
+        round_trip(&[0u16, u16::MAX]);
+        round_trip(&[0u32, u32::MAX]);
+        round_trip(&[0u64, u64::MAX]);
+        round_trip(&[true, false, true]);
+        #[cfg(feature = "half")]
+        round_trip(&[half::f16::from_f32(0.5), half::f16::MAX]);
+        #[cfg(feature = "num-complex")]
+        {
+            round_trip(&[num_complex::Complex32::new(1.0, -2.0)]);
+            round_trip(&[num_complex::Complex64::new(-1.0, 2.0)]);
+        }
+    }
+
+    #[test]
+    fn shapes_round_trip() {
+        for shape in [&[][..], &[0], &[2, 3], &[1, 2, 1, 3]] {
+            let len = shape.iter().product::<u64>() as usize;
+            let values: Vec<i32> = (0..len as i32).collect();
+            let array = Array::from_bytes(&Array::new(&values, shape).unwrap().to_bytes()).unwrap();
+            assert_eq!(array.shape(), shape);
+            assert_eq!(array.to_vec::<i32>().unwrap(), values);
+        }
+        assert!(matches!(
+            Array::new(&[1.0f32, 2.0], &[3]),
+            Err(Error::ShapeMismatch { len: 2, .. })
+        ));
+    }
+
+    // A version 1.0 file with the given header and data.
+    fn npy(header: &str, data: &[u8]) -> Vec<u8> {
+        let mut buf = MAGIC.to_vec();
+        buf.extend_from_slice(&[1, 0]);
+        buf.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        buf.extend_from_slice(header.as_bytes());
+        buf.extend_from_slice(data);
+        buf
+    }
+
+    #[test]
+    fn fortran_order_and_big_endian() {
+        // [[1, 2, 3], [4, 5, 6]] stored column by column.
+        let data: Vec<u8> = [1i32, 4, 2, 5, 3, 6]
+            .iter()
+            .flat_map(|v| v.to_be_bytes())
+            .collect();
+        let header = "{'descr': '>i4', 'fortran_order': True, 'shape': (2, 3), }\n";
+        let array = Array::from_bytes(&npy(header, &data)).unwrap();
+        assert_eq!(array.shape(), [2, 3]);
+        assert_eq!(array.to_vec::<i32>().unwrap(), [1, 2, 3, 4, 5, 6]);
+
+        // Three dimensions, the first index varying fastest.
+        let data: Vec<u8> = (0..12u8).collect();
+        let header = "{'descr': '|u1', 'fortran_order': True, 'shape': (2, 3, 2), }\n";
+        let array = Array::from_bytes(&npy(header, &data)).unwrap();
+        let c: Vec<u8> = array.to_vec().unwrap();
+        // Element [i, j, k] is at i + 2 * j + 6 * k in Fortran order.
+        assert_eq!(c[3 * 2 + 2 + 1], 1 + 2 + 6);
+        assert_eq!(c, [0, 6, 2, 8, 4, 10, 1, 7, 3, 9, 5, 11]);
+
+        let header = "{\"descr\": \"<f8\", \"fortran_order\": False, \"shape\": (1L,)}\n";
+        let array = Array::from_bytes(&npy(header, &2.5f64.to_le_bytes())).unwrap();
+        assert_eq!(array.to_vec::<f64>().unwrap(), [2.5]);
+    }
+
+    #[test]
+    fn truncated_and_bad_headers_are_errors() {
+        let bytes = Array::new(&[1.0f32, 2.0], &[2]).unwrap().to_bytes();
+        for len in 0..bytes.len() {
+            assert!(Array::from_bytes(&bytes[..len]).is_err(), "{} bytes", len);
+        }
+
+        let data = [0u8; 4];
+        let bad = |header: &str| Array::from_bytes(&npy(header, &data));
+        assert!(matches!(
+            bad("{'descr': '<f4', 'shape': (1,), }"),
+            Err(Error::Malformed("incomplete .npy header"))
+        ));
+        assert!(matches!(
+            bad("{'descr': '<f4', 'fortran_order': False, 'shape': (a,), }"),
+            Err(Error::Malformed("invalid .npy shape"))
+        ));
+        assert!(matches!(
+            bad("{'descr': '<f4', 'fortran_order': False, 'shape': 1, }"),
+            Err(Error::Malformed("invalid .npy shape"))
+        ));
+        assert!(matches!(
+            bad("{'descr': [('x', '<f4')], 'fortran_order': False, 'shape': (1,), }"),
+            Err(Error::Unsupported(_))
+        ));
+        assert!(matches!(
+            bad("{'descr': '<U8', 'fortran_order': False, 'shape': (1,), }"),
+            Err(Error::Unsupported(_))
+        ));
+        assert!(matches!(
+            bad("{'descr': '<f4', 'fortran_order': False, 'shape': (99999999999, 99999999999), }"),
+            Err(Error::Malformed(_))
+        ));
+
+        let mut buf = npy(
+            "{'descr': '<f4', 'fortran_order': False, 'shape': (1,), }",
+            &data,
+        );
+        buf[6] = 9;
+        assert!(matches!(
+            Array::from_bytes(&buf),
+            Err(Error::Unsupported(_))
+        ));
+        // A version 2.0 header length past the end of the file.
+        let mut buf = MAGIC.to_vec();
+        buf.extend_from_slice(&[2, 0]);
+        buf.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            Array::from_bytes(&buf),
+            Err(Error::Malformed("truncated .npy header"))
+        ));
+        assert!(Array::from_bytes(b"\x93NUMPZ\x01\x00\x00\x00").is_err());
+    }
+
+    #[test]
+    fn type_mismatch_agrees_with_the_crate_error() {
+        let array = Array::new(&[1.0f32], &[1]).unwrap();
+        let npy_error = array.to_vec::<i32>().unwrap_err();
+        assert!(matches!(
+            npy_error,
+            Error::TypeMismatch {
+                expected: DataType::Float,
+                actual: DataType::Int32,
+            }
+        ));
+        let spec = InputSpec::with_any_shape(DataType::Float);
+        let crate_error = spec.check(DataType::Int32, &[1]).unwrap_err();
+        assert!(matches!(
+            crate_error,
+            error::Error::TypeMismatch {
+                expected: DataType::Float,
+                actual: DataType::Int32,
+            }
+        ));
+        assert_eq!(npy_error.to_string(), crate_error.to_string());
+    }
+
+    #[test]
+    fn npz_round_trip() {
+        let a = Array::new(&[1.0f32, 2.0, 3.0, 4.0], &[2, 2]).unwrap();
+        let b = Array::new(&[7u8], &[]).unwrap();
+        let c = Array::new(&[-1i64, 1], &[2]).unwrap();
+        let buf = npz_to_bytes(&[("a", &a), ("b", &b), ("scores/c", &c)]).unwrap();
+        let arrays = npz_from_bytes(&buf).unwrap();
+        assert_eq!(
+            arrays,
+            [
+                ("a".to_string(), a),
+                ("b".to_string(), b),
+                ("scores/c".to_string(), c),
+            ]
+        );
+        assert!(npz_from_bytes(&npz_to_bytes(&[]).unwrap())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn corrupted_npz_is_an_error() {
+        let a = Array::new(&[1.0f32, 2.0], &[2]).unwrap();
+        let b = Array::new(&[3i32], &[1]).unwrap();
+        let buf = npz_to_bytes(&[("a", &a), ("b", &b)]).unwrap();
+        for len in 0..buf.len() {
+            assert!(npz_from_bytes(&buf[..len]).is_err(), "{} bytes", len);
+        }
+        // Flipping any byte must not panic. Most flips are caught by the
+        // signatures, the sizes or the CRC.
+        for i in 0..buf.len() {
+            let mut bad = buf.clone();
+            bad[i] ^= 0xff;
+            let _ = npz_from_bytes(&bad);
+        }
+        // A local header offset at the end of the address space.
+        let mut bad = buf.clone();
+        let directory = u32::from_le_bytes(buf[buf.len() - 6..buf.len() - 2].try_into().unwrap());
+        let offset = directory as usize + 42;
+        bad[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(npz_from_bytes(&bad).is_err());
+        // A corrupted element, right before the central directory.
+        let mut bad = buf;
+        bad[directory as usize - 1] ^= 1;
+        assert!(matches!(
+            npz_from_bytes(&bad),
+            Err(Error::Malformed("corrupted zip entry"))
+        ));
+    }
+}
@@ -0,0 +1,193 @@
+//! A pool of sessions created once from the same model.
+//!
+//! ```rust, ignore
+//! let pool = SessionPool::tflite(&mod_buf, 4);
+//! // For every request:
+//! let mut session = pool.get();
+//! session.add_input("input", &flat_img).run();
+//! let res: Vec<f32> = session.get_output("MobilenetV2/Predictions/Softmax");
+//! // The session is returned to the pool when `session` is dropped.
+//! ```
+
+use crate::{TFLiteSession, TFSession};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+// Sessions that can be reset before being handed out again.
+pub trait PoolSession {
+    fn reset(&mut self);
+}
+
+impl PoolSession for TFSession {
+    fn reset(&mut self) {
+        self.clear_input().clear_output();
+    }
+}
+
+// The TensorFlow-Lite host functions cannot clear inputs, so this does nothing:
+// a pooled session keeps the inputs of its previous user until `add_input`
+// overwrites them. Set every input of the model before each run.
+impl PoolSession for TFLiteSession {
+    fn reset(&mut self) {}
+}
+
+// Pool usage statistics.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PoolMetrics {
+    // Number of sessions handed out.
+    pub acquisitions: u64,
+    // Number of acquisitions that had to wait for a session to be returned.
+    pub waits: u64,
+    pub total_wait: Duration,
+    pub max_wait: Duration,
+}
+
+impl PoolMetrics {
+    // Mean wait time over all acquisitions.
+    pub fn average_wait(&self) -> Duration {
+        if self.acquisitions == 0 {
+            Duration::ZERO
+        } else {
+            let nanos = self.total_wait.as_nanos() / self.acquisitions as u128;
+            Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+        }
+    }
+}
+
+pub struct SessionPool<S> {
+    size: usize,
+    sessions: Mutex<Vec<S>>,
+    returned: Condvar,
+    metrics: Mutex<PoolMetrics>,
+}
+
+impl<S: PoolSession> SessionPool<S> {
+    // Create a pool of `size` sessions built by `create`.
+    pub fn new<F: FnMut() -> S>(size: usize, create: F) -> SessionPool<S> {
+        SessionPool {
+            size,
+            sessions: Mutex::new(std::iter::repeat_with(create).take(size).collect()),
+            returned: Condvar::new(),
+            metrics: Mutex::new(PoolMetrics::default()),
+        }
+    }
+
+    // Take a session, waiting for one to be returned if none is available.
+    // Without thread support (plain wasm32-wasi), waiting panics, so `get`
+    // panics on an exhausted pool; use `try_get` there.
+    pub fn get(&self) -> PooledSession<'_, S> {
+        let start = Instant::now();
+        let mut sessions = self.sessions.lock().unwrap();
+        let waited = sessions.is_empty();
+        while sessions.is_empty() {
+            sessions = self.returned.wait(sessions).unwrap();
+        }
+        let session = sessions.pop();
+        drop(sessions);
+        self.record(waited, start.elapsed());
+        PooledSession {
+            pool: self,
+            session,
+        }
+    }
+
+    // Take a session if one is available.
+    pub fn try_get(&self) -> Option<PooledSession<'_, S>> {
+        let session = self.sessions.lock().unwrap().pop()?;
+        self.record(false, Duration::ZERO);
+        Some(PooledSession {
+            pool: self,
+            session: Some(session),
+        })
+    }
+
+    // Number of sessions owned by the pool.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // Number of sessions currently available.
+    pub fn available(&self) -> usize {
+        self.sessions.lock().unwrap().len()
+    }
+
+    pub fn metrics(&self) -> PoolMetrics {
+        *self.metrics.lock().unwrap()
+    }
+
+    fn record(&self, waited: bool, wait: Duration) {
+        let mut metrics = self.metrics.lock().unwrap();
+        metrics.acquisitions += 1;
+        metrics.waits += waited as u64;
+        metrics.total_wait += wait;
+        metrics.max_wait = metrics.max_wait.max(wait);
+    }
+
+    fn put(&self, mut session: S) {
+        session.reset();
+        self.sessions.lock().unwrap().push(session);
+        self.returned.notify_one();
+    }
+}
+
+impl SessionPool<TFSession> {
+    // Create a pool of `size` TensorFlow sessions from a frozen graph.
+    pub fn tf(model_buf: &[u8], size: usize) -> SessionPool<TFSession> {
+        SessionPool::new(size, || TFSession::new(model_buf))
+    }
+}
+
+impl SessionPool<TFLiteSession> {
+    // Create a pool of `size` TensorFlow-Lite sessions from a model.
+    pub fn tflite(model_buf: &[u8], size: usize) -> SessionPool<TFLiteSession> {
+        SessionPool::new(size, || TFLiteSession::new(model_buf))
+    }
+}
+
+// A session borrowed from a pool, reset and returned when dropped.
+pub struct PooledSession<'a, S: PoolSession> {
+    pool: &'a SessionPool<S>,
+    session: Option<S>,
+}
+
+impl<S: PoolSession> Deref for PooledSession<'_, S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        self.session.as_ref().unwrap()
+    }
+}
+
+impl<S: PoolSession> DerefMut for PooledSession<'_, S> {
+    fn deref_mut(&mut self) -> &mut S {
+        self.session.as_mut().unwrap()
+    }
+}
+
+impl<S: PoolSession> Drop for PooledSession<'_, S> {
+    fn drop(&mut self) {
+        if let Some(session) = self.session.take() {
+            self.pool.put(session);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn average_wait_does_not_truncate() {
+        let metrics = PoolMetrics {
+            acquisitions: (1 << 32) + 2,
+            waits: 1,
+            total_wait: Duration::from_secs(3 << 32),
+            max_wait: Duration::from_secs(1),
+        };
+        assert_eq!(
+            metrics.average_wait(),
+            Duration::from_nanos(((3u128 << 32) * 1_000_000_000 / ((1 << 32) + 2)) as u64)
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Classification post-processing.
+//!
+//! ```rust, ignore
+//! let labels = Labels::from_file("labels.txt").unwrap();
+//! let scores: Vec<f32> = session.get_output("MobilenetV2/Predictions/Softmax");
+//! for p in classify(&scores, 5, Activation::None, &labels) {
+//!     println!("{} {:?} {}", p.class_id, p.label, p.score);
+//! }
+//! ```
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Label used for the extra class 0 of TF-slim style models.
+pub const BACKGROUND_LABEL: &str = "background";
+
+// Activation applied to the raw scores before ranking them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Activation {
+    // Scores are already probabilities (or only the ranking matters).
+    None,
+    // Single-label logits.
+    Softmax,
+    // Multi-label logits.
+    Sigmoid,
+}
+
+impl Activation {
+    pub fn apply(&self, scores: &[f32]) -> Vec<f32> {
+        match self {
+            Activation::None => scores.to_vec(),
+            Activation::Softmax => softmax(scores),
+            Activation::Sigmoid => sigmoid(scores),
+        }
+    }
+}
+
+// Numerically stable softmax.
+pub fn softmax(scores: &[f32]) -> Vec<f32> {
+    let max = scores.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    let exp: Vec<f32> = scores.iter().map(|s| (s - max).exp()).collect();
+    let sum: f32 = exp.iter().sum();
+    exp.into_iter().map(|e| e / sum).collect()
+}
+
+// Element-wise logistic function.
+pub fn sigmoid(scores: &[f32]) -> Vec<f32> {
+    scores.iter().map(|s| 1.0 / (1.0 + (-s).exp())).collect()
+}
+
+// Indices and scores of the `k` highest scores, best first.
+// Ties keep the lower index first and NaN scores are ranked last.
+pub fn top_k(scores: &[f32], k: usize) -> Vec<(usize, f32)> {
+    let mut ranked: Vec<(usize, f32)> = scores.iter().cloned().enumerate().collect();
+    ranked.sort_by(|a, b| match (a.1.is_nan(), b.1.is_nan()) {
+        (false, false) => b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)),
+        (nan_a, nan_b) => nan_a.cmp(&nan_b).then(a.0.cmp(&b.0)),
+    });
+    ranked.truncate(k);
+    ranked
+}
+
+// A class label table.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Labels {
+    names: Vec<Option<String>>,
+}
+
+impl Labels {
+    // Parse a label file. Two formats are accepted:
+    //   * plain text, one label per line, the line number being the class id;
+    //   * `id: name` lines, where ids may be sparse or out of order.
+    // The second format is used when every non-empty line starts with `<id>:`.
+    pub fn parse(text: &str) -> Labels {
+        let lines: Vec<&str> = text.lines().map(|l| l.trim_end_matches('\r')).collect();
+        let entries: Option<Vec<(usize, &str)>> = lines
+            .iter()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| {
+                let (id, name) = l.split_once(':')?;
+                Some((id.trim().parse::<usize>().ok()?, name.trim()))
+            })
+            .collect();
+
+        let mut names: Vec<Option<String>> = Vec::new();
+        match entries {
+            Some(entries) if !entries.is_empty() => {
+                for (id, name) in entries {
+                    if id >= names.len() {
+                        names.resize(id + 1, None);
+                    }
+                    names[id] = Some(name.to_string());
+                }
+            }
+            _ => {
+                names = lines
+                    .iter()
+                    .map(|l| Some(l.trim().to_string()).filter(|l| !l.is_empty()))
+                    .collect();
+                // Drop the trailing empty lines of the file.
+                while let Some(None) = names.last() {
+                    names.pop();
+                }
+            }
+        }
+        Labels { names }
+    }
+
+    // Read and parse a label file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Labels> {
+        Ok(Labels::parse(&fs::read_to_string(path)?))
+    }
+
+    // Number of class ids covered by the table.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    // Label of the class id, if any.
+    pub fn get(&self, id: usize) -> Option<&str> {
+        self.names.get(id).and_then(|n| n.as_deref())
+    }
+
+    // Offset between the model class ids and the label ids.
+    // TF-slim models output one more class than the label file holds,
+    // with class 0 being the background class.
+    pub fn offset_for(&self, num_classes: usize) -> usize {
+        if !self.is_empty() && num_classes == self.len() + 1 {
+            1
+        } else {
+            0
+        }
+    }
+
+    // Label of a model class id for a model with `num_classes` outputs.
+    pub fn label_for(&self, class_id: usize, num_classes: usize) -> Option<&str> {
+        match self.offset_for(num_classes) {
+            0 => self.get(class_id),
+            _ if class_id == 0 => Some(BACKGROUND_LABEL),
+            offset => self.get(class_id - offset),
+        }
+    }
+}
+
+// A ranked classification result.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Prediction<'a> {
+    // Class index in the model output.
+    pub class_id: usize,
+    pub score: f32,
+    pub label: Option<&'a str>,
+}
+
+// Apply the activation, rank the scores and look up the labels of the `k` best classes.
+pub fn classify<'a>(
+    scores: &[f32],
+    k: usize,
+    activation: Activation,
+    labels: &'a Labels,
+) -> Vec<Prediction<'a>> {
+    let scores = activation.apply(scores);
+    top_k(&scores, k)
+        .into_iter()
+        .map(|(class_id, score)| Prediction {
+            class_id,
+            score,
+            label: labels.label_for(class_id, scores.len()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-6, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn activations() {
+        assert_close(&softmax(&[0.0, 0.0]), &[0.5, 0.5]);
+        let p = softmax(&[1.0, 2.0, 3.0]);
+        assert_close(&p, &[0.09003057, 0.24472847, 0.66524096]);
+        // Large logits must not overflow.
+        assert_close(&softmax(&[1000.0, 1000.0]), &[0.5, 0.5]);
+        assert_close(&sigmoid(&[0.0, 2.0, -2.0]), &[0.5, 0.880797, 0.119203]);
+        assert_eq!(Activation::None.apply(&[3.0, -1.0]), [3.0, -1.0]);
+    }
+
+    #[test]
+    fn top_k_ranking() {
+        let scores = [0.1, f32::NAN, 0.7, 0.7, 0.3];
+        assert_eq!(top_k(&scores, 3), [(2, 0.7), (3, 0.7), (4, 0.3)]);
+        let all = top_k(&scores, 10);
+        assert_eq!(all.len(), 5);
+        assert_eq!(all[4].0, 1);
+    }
+
+    #[test]
+    fn plain_labels() {
+        let labels = Labels::parse("cat\r\ndog\n\nbird\n\n");
+        assert_eq!(labels.len(), 4);
+        assert_eq!(labels.get(1), Some("dog"));
+        assert_eq!(labels.get(2), None);
+        assert_eq!(labels.get(3), Some("bird"));
+    }
+
+    #[test]
+    fn indexed_labels() {
+        let labels = Labels::parse("1: person\n3: car\n");
+        assert_eq!(labels.len(), 4);
+        assert_eq!(labels.get(0), None);
+        assert_eq!(labels.get(1), Some("person"));
+        assert_eq!(labels.get(3), Some("car"));
+    }
+
+    #[test]
+    fn background_offset() {
+        let names: Vec<String> = (0..1000).map(|i| format!("class {}", i)).collect();
+        let labels = Labels::parse(&names.join("\n"));
+        // A model with 1001 outputs has an extra background class 0.
+        assert_eq!(labels.offset_for(1001), 1);
+        assert_eq!(labels.label_for(0, 1001), Some(BACKGROUND_LABEL));
+        assert_eq!(labels.label_for(1, 1001), Some("class 0"));
+        assert_eq!(labels.label_for(1000, 1001), Some("class 999"));
+        assert_eq!(labels.offset_for(1000), 0);
+        assert_eq!(labels.label_for(0, 1000), Some("class 0"));
+
+        // A label file with its own background row matches the model classes.
+        let labels = Labels::parse(&format!("background\n{}", names.join("\n")));
+        assert_eq!(labels.offset_for(1001), 0);
+        assert_eq!(labels.label_for(0, 1001), Some("background"));
+        assert_eq!(labels.label_for(1, 1001), Some("class 0"));
+    }
+
+    #[test]
+    fn classify_with_labels() {
+        let labels = Labels::parse("a\nb\nc");
+        let predictions = classify(&[1.0, 3.0, 2.0, 0.0], 2, Activation::Softmax, &labels);
+        assert_eq!(predictions.len(), 2);
+        assert_eq!(predictions[0].class_id, 1);
+        assert_eq!(predictions[0].label, Some("a"));
+        assert_eq!(predictions[1].class_id, 2);
+        assert_eq!(predictions[1].label, Some("b"));
+        assert!(predictions[0].score > predictions[1].score);
+    }
+}
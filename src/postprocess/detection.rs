@@ -0,0 +1,325 @@
+//! Object detection post-processing for the TF Object Detection API and
+//! the TFLite `TFLite_Detection_PostProcess` op.
+//!
+//! ```rust, ignore
+//! let outputs = DetectionOutputs::tflite_postprocess();
+//! session.add_input("normalized_input_image_tensor", &flat_img).run();
+//! let detections = outputs.decode_tflite(&session, 0.5, img_width, img_height);
+//! ```
+
+use crate::{TFLiteSession, TFSession};
+
+// An axis-aligned bounding box.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct BBox {
+    pub xmin: f32,
+    pub ymin: f32,
+    pub xmax: f32,
+    pub ymax: f32,
+}
+
+impl BBox {
+    pub fn new(xmin: f32, ymin: f32, xmax: f32, ymax: f32) -> BBox {
+        BBox {
+            xmin,
+            ymin,
+            xmax,
+            ymax,
+        }
+    }
+
+    // Build a box from the `[ymin, xmin, ymax, xmax]` layout used by TensorFlow.
+    pub fn from_yxyx(v: &[f32]) -> BBox {
+        BBox::new(v[1], v[0], v[3], v[2])
+    }
+
+    // Build a box from its center and size.
+    pub fn from_center(cx: f32, cy: f32, w: f32, h: f32) -> BBox {
+        BBox::new(cx - w / 2.0, cy - h / 2.0, cx + w / 2.0, cy + h / 2.0)
+    }
+
+    pub fn width(&self) -> f32 {
+        (self.xmax - self.xmin).max(0.0)
+    }
+
+    pub fn height(&self) -> f32 {
+        (self.ymax - self.ymin).max(0.0)
+    }
+
+    pub fn area(&self) -> f32 {
+        self.width() * self.height()
+    }
+
+    // Scale a normalized box to pixel coordinates of a `width` x `height` image.
+    pub fn to_pixels(&self, width: u32, height: u32) -> BBox {
+        let (w, h) = (width as f32, height as f32);
+        BBox::new(self.xmin * w, self.ymin * h, self.xmax * w, self.ymax * h)
+    }
+
+    // Clamp the box into `[0, width] x [0, height]`.
+    pub fn clamp(&self, width: f32, height: f32) -> BBox {
+        BBox::new(
+            self.xmin.clamp(0.0, width),
+            self.ymin.clamp(0.0, height),
+            self.xmax.clamp(0.0, width),
+            self.ymax.clamp(0.0, height),
+        )
+    }
+}
+
+// A detected object.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Detection {
+    pub bbox: BBox,
+    pub class_id: usize,
+    pub score: f32,
+}
+
+// Output tensor names of a detection model.
+// The TF Object Detection API reports 1-based class ids (as in its label maps),
+// `TFLite_Detection_PostProcess` reports 0-based ones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DetectionOutputs {
+    pub boxes: String,
+    pub classes: String,
+    pub scores: String,
+    pub num_detections: String,
+}
+
+impl DetectionOutputs {
+    // Output names of models exported with the TF Object Detection API.
+    pub fn object_detection_api() -> DetectionOutputs {
+        DetectionOutputs {
+            boxes: "detection_boxes".to_string(),
+            classes: "detection_classes".to_string(),
+            scores: "detection_scores".to_string(),
+            num_detections: "num_detections".to_string(),
+        }
+    }
+
+    // Output names of TFLite SSD models ending with `TFLite_Detection_PostProcess`.
+    pub fn tflite_postprocess() -> DetectionOutputs {
+        DetectionOutputs {
+            boxes: "TFLite_Detection_PostProcess".to_string(),
+            classes: "TFLite_Detection_PostProcess:1".to_string(),
+            scores: "TFLite_Detection_PostProcess:2".to_string(),
+            num_detections: "TFLite_Detection_PostProcess:3".to_string(),
+        }
+    }
+
+    // The four output names, to be registered with `TFSession::add_output` before running.
+    pub fn names(&self) -> [&str; 4] {
+        [
+            &self.boxes,
+            &self.classes,
+            &self.scores,
+            &self.num_detections,
+        ]
+    }
+
+    // Decode the outputs of a TensorFlow session into pixel coordinates.
+    pub fn decode_tf(
+        &self,
+        session: &TFSession,
+        score_threshold: f32,
+        width: u32,
+        height: u32,
+    ) -> Vec<Detection> {
+        self.decode_with(|name| session.get_output(name), score_threshold, width, height)
+    }
+
+    // Decode the outputs of a TensorFlow-Lite session into pixel coordinates.
+    pub fn decode_tflite(
+        &self,
+        session: &TFLiteSession,
+        score_threshold: f32,
+        width: u32,
+        height: u32,
+    ) -> Vec<Detection> {
+        self.decode_with(|name| session.get_output(name), score_threshold, width, height)
+    }
+
+    fn decode_with<F: Fn(&str) -> Vec<f32>>(
+        &self,
+        get_output: F,
+        score_threshold: f32,
+        width: u32,
+        height: u32,
+    ) -> Vec<Detection> {
+        let raw = self.names().map(get_output);
+        decode(raw, score_threshold)
+            .into_iter()
+            .map(|d| Detection {
+                bbox: d.bbox.to_pixels(width, height),
+                ..d
+            })
+            .collect()
+    }
+}
+
+// Decode the four detection outputs with normalized boxes.
+// Depending on the converter version, the TFLite post-process op emits its outputs
+// in different orders, so the roles are recovered from the tensor contents: the
+// count has a single element, the boxes have four per detection and the classes
+// are integral values. The given order is kept when the contents are ambiguous.
+pub fn decode(outputs: [Vec<f32>; 4], score_threshold: f32) -> Vec<Detection> {
+    let [boxes, classes, scores, num] = assign_roles(outputs);
+    let mut n = scores.len().min(classes.len()).min(boxes.len() / 4);
+    if let Some(count) = num.first() {
+        n = n.min(count.max(0.0) as usize);
+    }
+    (0..n)
+        .filter(|&i| scores[i] >= score_threshold)
+        .map(|i| Detection {
+            bbox: BBox::from_yxyx(&boxes[i * 4..i * 4 + 4]),
+            class_id: classes[i].max(0.0) as usize,
+            score: scores[i],
+        })
+        .collect()
+}
+
+fn assign_roles(outputs: [Vec<f32>; 4]) -> [Vec<f32>; 4] {
+    let mut rest: Vec<Vec<f32>> = outputs.into_iter().collect();
+    let max_len = rest.iter().map(|v| v.len()).max().unwrap_or(0);
+    let Some(boxes_pos) = rest
+        .iter()
+        .position(|v| v.len() == max_len && v.len() % 4 == 0 && !v.is_empty())
+    else {
+        return into_array(rest);
+    };
+    // With a single detection, the classes and scores have one element as the
+    // count does, so the count is the last one-element output holding a valid
+    // count, an integral value of at most the number of boxes.
+    let max_detections = (max_len / 4) as f32;
+    let singles: Vec<usize> = (0..4)
+        .filter(|&i| i != boxes_pos && rest[i].len() == 1)
+        .collect();
+    let is_count = |&i: &usize| {
+        let count = rest[i][0];
+        count.fract() == 0.0 && (0.0..=max_detections).contains(&count)
+    };
+    let Some(num_pos) = singles
+        .iter()
+        .rev()
+        .find(|i| is_count(i))
+        .or(singles.last())
+        .copied()
+    else {
+        return into_array(rest);
+    };
+    let num = rest.remove(num_pos);
+    let boxes = rest.remove(if boxes_pos > num_pos {
+        boxes_pos - 1
+    } else {
+        boxes_pos
+    });
+    let integral = |v: &Vec<f32>| v.iter().all(|x| x.fract() == 0.0);
+    if !integral(&rest[0]) && integral(&rest[1]) {
+        rest.swap(0, 1);
+    }
+    let scores = rest.pop().unwrap();
+    let classes = rest.pop().unwrap();
+    [boxes, classes, scores, num]
+}
+
+fn into_array(v: Vec<Vec<f32>>) -> [Vec<f32>; 4] {
+    v.try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boxes() -> Vec<f32> {
+        vec![0.1, 0.2, 0.5, 0.6, 0.0, 0.0, 1.0, 1.0, 0.3, 0.3, 0.4, 0.4]
+    }
+
+    #[test]
+    fn object_detection_api_order() {
+        let outputs = [
+            boxes(),
+            vec![1.0, 18.0, 3.0],
+            vec![0.9, 0.7, 0.2],
+            vec![2.0],
+        ];
+        let detections = decode(outputs, 0.5);
+        assert_eq!(
+            detections,
+            [
+                Detection {
+                    bbox: BBox::new(0.2, 0.1, 0.6, 0.5),
+                    class_id: 1,
+                    score: 0.9,
+                },
+                Detection {
+                    bbox: BBox::new(0.0, 0.0, 1.0, 1.0),
+                    class_id: 18,
+                    score: 0.7,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn tflite_output_orders() {
+        let expected = decode(
+            [
+                boxes(),
+                vec![0.0, 17.0, 2.0],
+                vec![0.9, 0.7, 0.2],
+                vec![3.0],
+            ],
+            0.0,
+        );
+        assert_eq!(expected.len(), 3);
+        // Newer converters emit the scores, boxes, count and classes.
+        let reordered = decode(
+            [
+                vec![0.9, 0.7, 0.2],
+                boxes(),
+                vec![3.0],
+                vec![0.0, 17.0, 2.0],
+            ],
+            0.0,
+        );
+        assert_eq!(reordered, expected);
+    }
+
+    #[test]
+    fn single_detection() {
+        let expected = [Detection {
+            bbox: BBox::new(0.2, 0.1, 0.6, 0.5),
+            class_id: 17,
+            score: 0.8,
+        }];
+        let bbox = vec![0.1, 0.2, 0.5, 0.6];
+        let outputs = [bbox.clone(), vec![17.0], vec![0.8], vec![1.0]];
+        assert_eq!(decode(outputs, 0.5), expected);
+        // The classes come last, so they must not be taken for the count.
+        let outputs = [vec![0.8], bbox, vec![1.0], vec![17.0]];
+        assert_eq!(decode(outputs, 0.5), expected);
+    }
+
+    #[test]
+    fn count_limits_detections() {
+        let outputs = [boxes(), vec![1.0, 2.0, 3.0], vec![0.9, 0.8, 0.7], vec![1.0]];
+        assert_eq!(decode(outputs, 0.0).len(), 1);
+        let outputs = [boxes(), vec![1.0, 2.0, 3.0], vec![0.9, 0.8, 0.7], vec![0.0]];
+        assert!(decode(outputs, 0.0).is_empty());
+    }
+
+    #[test]
+    fn bbox_helpers() {
+        let bbox = BBox::from_center(0.5, 0.5, 0.2, 0.4);
+        assert_eq!(bbox, BBox::new(0.4, 0.3, 0.6, 0.7));
+        assert_eq!(
+            BBox::new(0.25, 0.5, 0.75, 1.0).to_pixels(200, 100),
+            BBox::new(50.0, 50.0, 150.0, 100.0)
+        );
+        assert_eq!(
+            BBox::new(-5.0, 10.0, 20.0, 50.0).clamp(15.0, 40.0).area(),
+            450.0
+        );
+        assert_eq!(BBox::new(1.0, 1.0, 0.0, 0.0).area(), 0.0);
+    }
+}
@@ -0,0 +1,25 @@
+//! Helpers for turning raw output tensors into application results.
+
+pub mod classification;
+pub mod detection;
+pub mod nms;
+pub mod pose;
+pub mod segmentation;
+pub mod yolo;
+
+use crate::error::{Error, Result};
+
+// Check that a tensor of `len` values has the given dimensions.
+fn check_len(len: usize, dims: &[usize]) -> Result<()> {
+    let expected = dims
+        .iter()
+        .try_fold(1usize, |n, d| n.checked_mul(*d))
+        .ok_or(Error::TooLarge)?;
+    if len != expected {
+        return Err(Error::LengthMismatch {
+            expected,
+            actual: len,
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,186 @@
+//! Box overlap utilities and non-maximum suppression.
+
+use super::detection::{BBox, Detection};
+
+// Intersection area of two boxes.
+pub fn intersection(a: &BBox, b: &BBox) -> f32 {
+    let w = a.xmax.min(b.xmax) - a.xmin.max(b.xmin);
+    let h = a.ymax.min(b.ymax) - a.ymin.max(b.ymin);
+    if w <= 0.0 || h <= 0.0 {
+        0.0
+    } else {
+        w * h
+    }
+}
+
+// Intersection over union of two boxes.
+pub fn iou(a: &BBox, b: &BBox) -> f32 {
+    let inter = intersection(a, b);
+    let union = a.area() + b.area() - inter;
+    if union <= 0.0 {
+        0.0
+    } else {
+        inter / union
+    }
+}
+
+// How overlapping boxes are suppressed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Suppression {
+    // Drop boxes overlapping a kept box by more than the threshold.
+    Hard { iou_threshold: f32 },
+    // Soft-NMS: scale the score by `1 - iou` when the overlap exceeds the threshold.
+    Linear { iou_threshold: f32 },
+    // Soft-NMS: scale the score by `exp(-iou^2 / sigma)`. A `sigma` of zero or
+    // less, the limit of small values, drops every overlapping box.
+    Gaussian { sigma: f32 },
+}
+
+// Run non-maximum suppression and return the kept detections, best first.
+// With `class_aware`, only boxes of the same class suppress each other.
+// Detections whose (decayed) score falls below `score_threshold` are dropped.
+pub fn non_max_suppression(
+    detections: Vec<Detection>,
+    suppression: Suppression,
+    class_aware: bool,
+    score_threshold: f32,
+) -> Vec<Detection> {
+    let mut pending: Vec<Detection> = detections
+        .into_iter()
+        .filter(|d| d.score >= score_threshold)
+        .collect();
+    let mut kept = Vec::new();
+    while !pending.is_empty() {
+        let best = pending
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.score.total_cmp(&b.1.score).then(b.0.cmp(&a.0)))
+            .map(|(i, _)| i)
+            .unwrap();
+        let best = pending.swap_remove(best);
+        pending.retain_mut(|d| {
+            if class_aware && d.class_id != best.class_id {
+                return true;
+            }
+            let overlap = iou(&best.bbox, &d.bbox);
+            match suppression {
+                Suppression::Hard { iou_threshold } => overlap <= iou_threshold,
+                Suppression::Linear { iou_threshold } => {
+                    if overlap > iou_threshold {
+                        d.score *= 1.0 - overlap;
+                    }
+                    d.score >= score_threshold
+                }
+                Suppression::Gaussian { sigma } if sigma > 0.0 => {
+                    d.score *= (-(overlap * overlap) / sigma).exp();
+                    d.score >= score_threshold
+                }
+                Suppression::Gaussian { .. } => overlap == 0.0,
+            }
+        });
+        kept.push(best);
+    }
+    kept
+}
+
+// Classic hard NMS.
+pub fn nms(detections: Vec<Detection>, iou_threshold: f32, class_aware: bool) -> Vec<Detection> {
+    non_max_suppression(
+        detections,
+        Suppression::Hard { iou_threshold },
+        class_aware,
+        f32::NEG_INFINITY,
+    )
+}
+
+// Gaussian soft-NMS.
+pub fn soft_nms(
+    detections: Vec<Detection>,
+    sigma: f32,
+    score_threshold: f32,
+    class_aware: bool,
+) -> Vec<Detection> {
+    non_max_suppression(
+        detections,
+        Suppression::Gaussian { sigma },
+        class_aware,
+        score_threshold,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detection(xmin: f32, score: f32, class_id: usize) -> Detection {
+        Detection {
+            bbox: BBox::new(xmin, 0.0, xmin + 10.0, 10.0),
+            class_id,
+            score,
+        }
+    }
+
+    #[test]
+    fn overlap() {
+        let a = BBox::new(0.0, 0.0, 10.0, 10.0);
+        let b = BBox::new(5.0, 0.0, 15.0, 10.0);
+        assert_eq!(intersection(&a, &b), 50.0);
+        assert!((iou(&a, &b) - 1.0 / 3.0).abs() < 1e-6);
+        assert_eq!(iou(&a, &a), 1.0);
+        assert_eq!(iou(&a, &BBox::new(10.0, 0.0, 20.0, 10.0)), 0.0);
+        assert_eq!(iou(&BBox::default(), &BBox::default()), 0.0);
+    }
+
+    #[test]
+    fn hard_nms() {
+        let detections = vec![
+            detection(0.0, 0.8, 0),
+            detection(1.0, 0.9, 0),
+            detection(2.0, 0.7, 1),
+            detection(30.0, 0.6, 0),
+        ];
+        let kept = nms(detections.clone(), 0.5, false);
+        assert_eq!(kept, [detection(1.0, 0.9, 0), detection(30.0, 0.6, 0)]);
+        let kept = nms(detections, 0.5, true);
+        assert_eq!(
+            kept,
+            [
+                detection(1.0, 0.9, 0),
+                detection(2.0, 0.7, 1),
+                detection(30.0, 0.6, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn soft_nms_decays_scores() {
+        let detections = vec![detection(0.0, 0.9, 0), detection(5.0, 0.8, 0)];
+        let kept = soft_nms(detections.clone(), 0.5, 0.1, false);
+        assert_eq!(kept.len(), 2);
+        let decay = (-(1.0f32 / 9.0) / 0.5).exp();
+        assert!((kept[1].score - 0.8 * decay).abs() < 1e-6);
+        // The decayed score falls below the threshold.
+        assert_eq!(soft_nms(detections.clone(), 0.5, 0.7, false).len(), 1);
+
+        let kept = non_max_suppression(
+            detections,
+            Suppression::Linear { iou_threshold: 0.3 },
+            false,
+            0.0,
+        );
+        assert!((kept[1].score - 0.8 * (1.0 - 1.0 / 3.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn soft_nms_without_sigma() {
+        let detections = vec![
+            detection(0.0, 0.9, 0),
+            detection(5.0, 0.8, 0),
+            detection(20.0, 0.7, 0),
+        ];
+        for sigma in [0.0, -1.0, f32::NAN] {
+            let kept = soft_nms(detections.clone(), sigma, 0.0, false);
+            assert_eq!(kept, [detection(0.0, 0.9, 0), detection(20.0, 0.7, 0)]);
+        }
+    }
+}
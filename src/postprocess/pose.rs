@@ -0,0 +1,321 @@
+//! Pose estimation post-processing for MoveNet and PoseNet models.
+//!
+//! ```rust, ignore
+//! let output: Vec<f32> = session.get_output("output_0");
+//! let pose = decode_movenet(&output).map(&InputTransform::stretch(img_width, img_height));
+//! for (a, b) in SKELETON {
+//!     draw_line(&pose.keypoints[a], &pose.keypoints[b]);
+//! }
+//! ```
+
+use super::check_len;
+use super::classification::sigmoid;
+use crate::error::Result;
+
+// The 17 COCO keypoints, in model output order.
+pub const KEYPOINT_NAMES: [&str; 17] = [
+    "nose",
+    "left_eye",
+    "right_eye",
+    "left_ear",
+    "right_ear",
+    "left_shoulder",
+    "right_shoulder",
+    "left_elbow",
+    "right_elbow",
+    "left_wrist",
+    "right_wrist",
+    "left_hip",
+    "right_hip",
+    "left_knee",
+    "right_knee",
+    "left_ankle",
+    "right_ankle",
+];
+
+// Keypoint index pairs forming the standard skeleton.
+pub const SKELETON: [(usize, usize); 18] = [
+    (0, 1),
+    (0, 2),
+    (1, 3),
+    (2, 4),
+    (0, 5),
+    (0, 6),
+    (5, 7),
+    (7, 9),
+    (6, 8),
+    (8, 10),
+    (5, 6),
+    (5, 11),
+    (6, 12),
+    (11, 12),
+    (11, 13),
+    (13, 15),
+    (12, 14),
+    (14, 16),
+];
+
+// A named keypoint. Decoders return coordinates normalized to the model input,
+// `Pose::map` converts them to original image pixels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Keypoint {
+    pub name: &'static str,
+    pub x: f32,
+    pub y: f32,
+    pub score: f32,
+}
+
+// A set of keypoints belonging to one person.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pose {
+    pub keypoints: Vec<Keypoint>,
+    pub score: f32,
+}
+
+impl Pose {
+    fn new(points: impl Iterator<Item = (f32, f32, f32)>) -> Pose {
+        let keypoints: Vec<Keypoint> = points
+            .zip(KEYPOINT_NAMES)
+            .map(|((x, y, score), name)| Keypoint { name, x, y, score })
+            .collect();
+        let score = keypoints.iter().map(|k| k.score).sum::<f32>() / keypoints.len().max(1) as f32;
+        Pose { keypoints, score }
+    }
+
+    // Map the keypoints back to original image coordinates.
+    pub fn map(&self, transform: &InputTransform) -> Pose {
+        let keypoints = self
+            .keypoints
+            .iter()
+            .map(|k| {
+                let (x, y) = transform.apply(k.x, k.y);
+                Keypoint { x, y, ..*k }
+            })
+            .collect();
+        Pose {
+            keypoints,
+            score: self.score,
+        }
+    }
+
+    // Keypoint by name.
+    pub fn get(&self, name: &str) -> Option<&Keypoint> {
+        self.keypoints.iter().find(|k| k.name == name)
+    }
+}
+
+// The rectangle of the original image that was resized into the model input.
+// It lies partly outside the image when the input was letterboxed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InputTransform {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl InputTransform {
+    // The whole image was resized to the input size, as done by the image loaders.
+    pub fn stretch(img_width: u32, img_height: u32) -> InputTransform {
+        InputTransform::crop(0, 0, img_width, img_height)
+    }
+
+    // A region of the image was cropped and resized to the input size.
+    pub fn crop(x: u32, y: u32, width: u32, height: u32) -> InputTransform {
+        InputTransform {
+            x: x as f32,
+            y: y as f32,
+            width: width as f32,
+            height: height as f32,
+        }
+    }
+
+    // The image was scaled to fit the input keeping its aspect ratio, and centered with padding.
+    pub fn letterbox(
+        img_width: u32,
+        img_height: u32,
+        input_width: u32,
+        input_height: u32,
+    ) -> InputTransform {
+        let scale =
+            (input_width as f32 / img_width as f32).min(input_height as f32 / img_height as f32);
+        let width = input_width as f32 / scale;
+        let height = input_height as f32 / scale;
+        InputTransform {
+            x: (img_width as f32 - width) / 2.0,
+            y: (img_height as f32 - height) / 2.0,
+            width,
+            height,
+        }
+    }
+
+    // Map a point normalized to the model input to original image pixels.
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.x + x * self.width, self.y + y * self.height)
+    }
+}
+
+// Decode the `[1, 1, 17, 3]` (y, x, score) output of single-pose MoveNet.
+pub fn decode_movenet(output: &[f32]) -> Pose {
+    Pose::new(output.chunks_exact(3).map(|k| (k[1], k[0], k[2])))
+}
+
+// Decode the `[1, 6, 56]` output of multi-pose MoveNet: 17 (y, x, score) keypoints
+// followed by a (ymin, xmin, ymax, xmax, score) box per person.
+pub fn decode_movenet_multipose(output: &[f32], min_score: f32) -> Vec<Pose> {
+    output
+        .chunks_exact(56)
+        .filter(|p| p[55] >= min_score)
+        .map(|p| Pose {
+            score: p[55],
+            ..decode_movenet(&p[..51])
+        })
+        .collect()
+}
+
+// Decode single-pose PoseNet outputs: `[1, height, width, 17]` heatmap logits and
+// `[1, height, width, 34]` offsets (17 y offsets, then 17 x offsets) in input pixels.
+// Outputs of other sizes are a `LengthMismatch` error, and an empty grid gives a
+// pose without keypoints.
+pub fn decode_posenet(
+    heatmaps: &[f32],
+    offsets: &[f32],
+    height: usize,
+    width: usize,
+    output_stride: usize,
+) -> Result<Pose> {
+    let n = KEYPOINT_NAMES.len();
+    check_len(heatmaps.len(), &[height, width, n])?;
+    check_len(offsets.len(), &[height, width, 2 * n])?;
+    if height == 0 || width == 0 {
+        return Ok(Pose::new(std::iter::empty()));
+    }
+    let input_width = ((width - 1) * output_stride + 1) as f32;
+    let input_height = ((height - 1) * output_stride + 1) as f32;
+    Ok(Pose::new((0..n).map(|k| {
+        let mut best = 0;
+        for cell in 0..width * height {
+            if heatmaps[cell * n + k] > heatmaps[best * n + k] {
+                best = cell;
+            }
+        }
+        let (row, col) = (best / width, best % width);
+        let y = (row * output_stride) as f32 + offsets[best * 2 * n + k];
+        let x = (col * output_stride) as f32 + offsets[best * 2 * n + n + k];
+        let score = sigmoid(&[heatmaps[best * n + k]])[0];
+        (x / input_width, y / input_height, score)
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    // A MoveNet output with keypoint `k` at `(x, y) = (k / 20, k / 40)`.
+    fn movenet_output(score: f32) -> Vec<f32> {
+        (0..17)
+            .flat_map(|k| [k as f32 / 40.0, k as f32 / 20.0, score])
+            .collect()
+    }
+
+    #[test]
+    fn movenet_keypoints() {
+        let pose = decode_movenet(&movenet_output(0.5));
+        assert_eq!(pose.keypoints.len(), 17);
+        assert_eq!(pose.score, 0.5);
+        let wrist = pose.get("left_wrist").unwrap();
+        assert_eq!((wrist.x, wrist.y, wrist.score), (0.45, 0.225, 0.5));
+
+        // The image was 640x480, letterboxed into the square input.
+        let transform = InputTransform::letterbox(640, 480, 192, 192);
+        assert_eq!(
+            transform,
+            InputTransform {
+                x: 0.0,
+                y: -80.0,
+                width: 640.0,
+                height: 640.0
+            }
+        );
+        let wrist = *pose.map(&transform).get("left_wrist").unwrap();
+        assert!((wrist.x - 288.0).abs() < 1e-3 && (wrist.y - 64.0).abs() < 1e-3);
+        let nose = pose.map(&InputTransform::stretch(640, 480)).keypoints[0];
+        assert_eq!((nose.x, nose.y), (0.0, 0.0));
+    }
+
+    #[test]
+    fn movenet_multipose() {
+        let mut output = Vec::new();
+        for score in [0.9, 0.1] {
+            output.extend(movenet_output(score));
+            output.extend([0.0, 0.0, 1.0, 1.0, score]);
+        }
+        let poses = decode_movenet_multipose(&output, 0.5);
+        assert_eq!(poses.len(), 1);
+        assert_eq!(poses[0].score, 0.9);
+        assert_eq!(poses[0].keypoints[16].x, 0.8);
+    }
+
+    #[test]
+    fn crop_transform() {
+        let transform = InputTransform::crop(100, 50, 200, 100);
+        assert_eq!(transform.apply(0.0, 0.0), (100.0, 50.0));
+        assert_eq!(transform.apply(0.5, 0.25), (200.0, 75.0));
+        assert_eq!(transform.apply(1.0, 1.0), (300.0, 150.0));
+    }
+
+    #[test]
+    fn posenet_keypoints() {
+        // A 2x3 grid with every keypoint peaking in the cell of row 1, column 2.
+        let (height, width, stride) = (2, 3, 16);
+        let mut heatmaps = vec![-4.0; height * width * 17];
+        let mut offsets = vec![0.0; height * width * 34];
+        let best = width + 2;
+        for k in 0..17 {
+            heatmaps[best * 17 + k] = 0.0;
+            offsets[best * 34 + k] = 1.0;
+            offsets[best * 34 + 17 + k] = -2.0;
+        }
+        let pose = decode_posenet(&heatmaps, &offsets, height, width, stride).unwrap();
+        // The input is 33x17 pixels and the keypoints are at (30, 17).
+        let nose = pose.keypoints[0];
+        assert_eq!((nose.x, nose.y, nose.score), (30.0 / 33.0, 1.0, 0.5));
+        let nose = pose.map(&InputTransform::stretch(330, 170)).keypoints[0];
+        assert!((nose.x - 300.0).abs() < 1e-3 && (nose.y - 170.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn posenet_rejects_mismatched_outputs() {
+        assert!(matches!(
+            decode_posenet(&[0.0; 17], &[0.0; 10], 1, 1, 16),
+            Err(Error::LengthMismatch {
+                expected: 34,
+                actual: 10
+            })
+        ));
+        assert!(matches!(
+            decode_posenet(&[0.0; 17], &[0.0; 34], 2, 1, 16),
+            Err(Error::LengthMismatch {
+                expected: 34,
+                actual: 17
+            })
+        ));
+        let pose = decode_posenet(&[], &[], 0, 4, 16).unwrap();
+        assert!(pose.keypoints.is_empty());
+    }
+
+    #[test]
+    fn skeleton() {
+        for (a, b) in SKELETON {
+            assert!(a < KEYPOINT_NAMES.len() && b < KEYPOINT_NAMES.len() && a != b);
+        }
+        let names: Vec<(&str, &str)> = SKELETON
+            .iter()
+            .map(|(a, b)| (KEYPOINT_NAMES[*a], KEYPOINT_NAMES[*b]))
+            .collect();
+        assert!(names.contains(&("left_shoulder", "left_elbow")));
+        assert!(names.contains(&("right_knee", "right_ankle")));
+        assert!(names.contains(&("left_hip", "right_hip")));
+    }
+}
@@ -0,0 +1,208 @@
+//! Semantic segmentation post-processing for DeepLab-style models.
+//!
+//! ```rust, ignore
+//! let logits: Vec<f32> = session.get_output("logits");
+//! let mask = SegmentationMask::from_logits(&logits, 257, 257, 21).unwrap().resize(img_height, img_width);
+//! let overlay = mask.overlay(&img_rgb8, &pascal_voc_palette(21), 0.5);
+//! ```
+
+use super::check_len;
+use crate::error::Result;
+
+// A per-pixel class map, stored row-major. Sizes are given as `(height, width)`,
+// like the dimensions of the tensors.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SegmentationMask {
+    pub width: usize,
+    pub height: usize,
+    pub classes: Vec<u32>,
+}
+
+impl SegmentationMask {
+    // Per-pixel argmax of `[1, height, width, num_classes]` logits or probabilities.
+    // Returns an empty mask when `num_classes` is 0, and a `LengthMismatch`
+    // error when `logits` do not hold that many values.
+    pub fn from_logits(
+        logits: &[f32],
+        height: usize,
+        width: usize,
+        num_classes: usize,
+    ) -> Result<SegmentationMask> {
+        if num_classes == 0 {
+            return Ok(SegmentationMask::default());
+        }
+        check_len(logits.len(), &[height, width, num_classes])?;
+        let classes = logits
+            .chunks_exact(num_classes)
+            .map(|pixel| {
+                let mut best = 0;
+                for (c, v) in pixel.iter().enumerate() {
+                    if *v > pixel[best] {
+                        best = c;
+                    }
+                }
+                best as u32
+            })
+            .collect();
+        Ok(SegmentationMask {
+            width,
+            height,
+            classes,
+        })
+    }
+
+    // Wrap a `[1, height, width]` class map, such as the int64 output of
+    // DeepLab's `SemanticPredictions`. Negative ids are mapped to class 0.
+    // Returns a `LengthMismatch` error when `map` does not hold `height * width`
+    // values.
+    pub fn from_class_map<T: Copy + TryInto<u32>>(
+        map: &[T],
+        height: usize,
+        width: usize,
+    ) -> Result<SegmentationMask> {
+        check_len(map.len(), &[height, width])?;
+        let classes = map.iter().map(|c| (*c).try_into().unwrap_or(0)).collect();
+        Ok(SegmentationMask {
+            width,
+            height,
+            classes,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+
+    // Nearest-neighbour resize, e.g. back to the original image size.
+    // An empty mask stays empty.
+    pub fn resize(&self, height: usize, width: usize) -> SegmentationMask {
+        if self.width == 0 || self.height == 0 {
+            return SegmentationMask::default();
+        }
+        let mut classes = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let sy = ((y * 2 + 1) * self.height / (height * 2)).min(self.height - 1);
+            for x in 0..width {
+                let sx = ((x * 2 + 1) * self.width / (width * 2)).min(self.width - 1);
+                classes.push(self.classes[sy * self.width + sx]);
+            }
+        }
+        SegmentationMask {
+            width,
+            height,
+            classes,
+        }
+    }
+
+    // Pixel count of every class id, indexed by class id.
+    pub fn class_areas(&self) -> Vec<usize> {
+        let mut areas = Vec::new();
+        for c in &self.classes {
+            let c = *c as usize;
+            if c >= areas.len() {
+                areas.resize(c + 1, 0);
+            }
+            areas[c] += 1;
+        }
+        areas
+    }
+
+    // Render the mask into an RGB8 buffer with the same layout as
+    // `load_jpg_image_to_rgb8`. Class ids beyond the palette wrap around, and an
+    // empty palette renders nothing.
+    pub fn colorize(&self, palette: &[[u8; 3]]) -> Vec<u8> {
+        if palette.is_empty() {
+            return Vec::new();
+        }
+        self.classes
+            .iter()
+            .flat_map(|c| palette[*c as usize % palette.len()])
+            .collect()
+    }
+
+    // Blend the colorized mask over an RGB8 image of the same size.
+    // `alpha` is the weight of the mask color. An empty palette gives an empty
+    // buffer.
+    pub fn overlay(&self, image: &[u8], palette: &[[u8; 3]], alpha: f32) -> Vec<u8> {
+        image
+            .iter()
+            .zip(self.colorize(palette))
+            .map(|(i, m)| (*i as f32 * (1.0 - alpha) + m as f32 * alpha).round() as u8)
+            .collect()
+    }
+}
+
+// The PASCAL VOC color palette used by DeepLab, for `n` classes.
+pub fn pascal_voc_palette(n: usize) -> Vec<[u8; 3]> {
+    (0..n)
+        .map(|i| {
+            let mut color = [0u8; 3];
+            let mut id = i;
+            for shift in (0..8).rev() {
+                for (ch, c) in color.iter_mut().enumerate() {
+                    *c |= (((id >> ch) & 1) as u8) << shift;
+                }
+                id >>= 3;
+            }
+            color
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn resize_takes_height_then_width() {
+        let mask = SegmentationMask::from_class_map(&[0i64, 1, 2, 3, 4, 5], 2, 3).unwrap();
+        let resized = mask.resize(4, 3);
+        assert_eq!((resized.height, resized.width), (4, 3));
+        assert_eq!(resized.classes, [0, 1, 2, 0, 1, 2, 3, 4, 5, 3, 4, 5]);
+    }
+
+    #[test]
+    fn degenerate_inputs_give_empty_results() {
+        assert!(SegmentationMask::from_logits(&[0.5, 0.5], 1, 1, 0)
+            .unwrap()
+            .is_empty());
+        assert!(SegmentationMask::default().resize(4, 4).is_empty());
+        let mask = SegmentationMask::from_class_map(&[1u8, 0], 1, 2).unwrap();
+        assert!(mask.colorize(&[]).is_empty());
+        assert!(mask.overlay(&[0; 6], &[], 0.5).is_empty());
+    }
+
+    #[test]
+    fn argmax_of_logits() {
+        let logits = [0.1, 0.9, 0.7, 0.3, -1.0, -2.0, 5.0, 5.0];
+        let mask = SegmentationMask::from_logits(&logits, 2, 2, 2).unwrap();
+        assert_eq!(mask.classes, [1, 0, 0, 0]);
+        assert_eq!(mask.class_areas(), [3, 1]);
+        let mask = SegmentationMask::from_class_map(&[-1i64, 2, 2, 0], 2, 2).unwrap();
+        assert_eq!(mask.classes, [0, 2, 2, 0]);
+        assert_eq!(mask.class_areas(), [2, 0, 2]);
+    }
+
+    #[test]
+    fn short_inputs_are_rejected() {
+        assert!(matches!(
+            SegmentationMask::from_class_map(&[0u8], 2, 2),
+            Err(Error::LengthMismatch {
+                expected: 4,
+                actual: 1
+            })
+        ));
+        assert!(matches!(
+            SegmentationMask::from_logits(&[0.0, 1.0], 2, 2, 2),
+            Err(Error::LengthMismatch {
+                expected: 8,
+                actual: 2
+            })
+        ));
+        assert!(matches!(
+            SegmentationMask::from_class_map(&[0u8], usize::MAX, 2),
+            Err(Error::TooLarge)
+        ));
+    }
+}
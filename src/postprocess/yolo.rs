@@ -0,0 +1,163 @@
+//! Decoding of raw YOLO outputs exported without a post-process op.
+//!
+//! ```rust, ignore
+//! let output: Vec<f32> = session.get_output("output0");
+//! let detections = YoloDecoder::yolov8(80).decode(&output, 0.25).unwrap();
+//! let detections = nms(detections, 0.45, true);
+//! ```
+
+use super::detection::{BBox, Detection};
+use crate::error::{Error, Result};
+
+// Layout description of a YOLO output tensor.
+// Boxes are `[cx, cy, w, h]` in the coordinates of the model input.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct YoloDecoder {
+    pub num_classes: usize,
+    // Whether a box objectness score precedes the class scores.
+    pub objectness: bool,
+    // Whether the tensor is `[1, 4 + C, N]` instead of `[1, N, 4 + C]`.
+    pub channels_first: bool,
+}
+
+impl YoloDecoder {
+    // YOLOv5 layout: `[1, N, 5 + C]` with objectness.
+    pub fn yolov5(num_classes: usize) -> YoloDecoder {
+        YoloDecoder {
+            num_classes,
+            objectness: true,
+            channels_first: false,
+        }
+    }
+
+    // YOLOv8 layout: `[1, 4 + C, N]` without objectness.
+    pub fn yolov8(num_classes: usize) -> YoloDecoder {
+        YoloDecoder {
+            num_classes,
+            objectness: false,
+            channels_first: true,
+        }
+    }
+
+    // Values per box.
+    pub fn channels(&self) -> usize {
+        4 + self.objectness as usize + self.num_classes
+    }
+
+    // Decode the boxes whose best class confidence is at least `conf_threshold`.
+    // The result still needs non-maximum suppression. An output which does not
+    // hold a whole number of boxes is a `LengthMismatch` error.
+    pub fn decode(&self, output: &[f32], conf_threshold: f32) -> Result<Vec<Detection>> {
+        let channels = self.channels();
+        let num_boxes = output.len() / channels;
+        if num_boxes * channels != output.len() {
+            return Err(Error::LengthMismatch {
+                expected: num_boxes * channels,
+                actual: output.len(),
+            });
+        }
+        let at = |b: usize, c: usize| {
+            if self.channels_first {
+                output[c * num_boxes + b]
+            } else {
+                output[b * channels + c]
+            }
+        };
+        let class_offset = 4 + self.objectness as usize;
+        let mut detections = Vec::new();
+        for b in 0..num_boxes {
+            let objectness = if self.objectness { at(b, 4) } else { 1.0 };
+            if objectness < conf_threshold {
+                continue;
+            }
+            let mut class_id = 0;
+            let mut class_score = f32::NEG_INFINITY;
+            for c in 0..self.num_classes {
+                let s = at(b, class_offset + c);
+                if s > class_score {
+                    class_id = c;
+                    class_score = s;
+                }
+            }
+            let score = if self.num_classes == 0 {
+                objectness
+            } else {
+                objectness * class_score
+            };
+            if score < conf_threshold {
+                continue;
+            }
+            detections.push(Detection {
+                bbox: BBox::from_center(at(b, 0), at(b, 1), at(b, 2), at(b, 3)),
+                class_id,
+                score,
+            });
+        }
+        Ok(detections)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yolov5_layout() {
+        // Two boxes of `[cx, cy, w, h, objectness, class 0, class 1]`.
+        let output = [
+            50.0, 40.0, 20.0, 10.0, 0.9, 0.2, 0.8, //
+            10.0, 10.0, 4.0, 4.0, 0.1, 0.9, 0.1,
+        ];
+        let detections = YoloDecoder::yolov5(2).decode(&output, 0.25).unwrap();
+        assert_eq!(detections.len(), 1);
+        assert_eq!(detections[0].bbox, BBox::new(40.0, 35.0, 60.0, 45.0));
+        assert_eq!(detections[0].class_id, 1);
+        assert!((detections[0].score - 0.72).abs() < 1e-6);
+    }
+
+    #[test]
+    fn yolov8_layout() {
+        // Two boxes, one channel per row: `cx, cy, w, h, class 0, class 1`.
+        let output = [
+            50.0, 10.0, //
+            40.0, 10.0, //
+            20.0, 4.0, //
+            10.0, 4.0, //
+            0.3, 0.1, //
+            0.6, 0.2,
+        ];
+        let decoder = YoloDecoder::yolov8(2);
+        assert_eq!(decoder.channels(), 6);
+        let detections = decoder.decode(&output, 0.25).unwrap();
+        assert_eq!(
+            detections,
+            [Detection {
+                bbox: BBox::new(40.0, 35.0, 60.0, 45.0),
+                class_id: 1,
+                score: 0.6,
+            }]
+        );
+    }
+
+    #[test]
+    fn truncated_output() {
+        assert!(matches!(
+            YoloDecoder::yolov8(2).decode(&[0.5; 13], 0.25),
+            Err(Error::LengthMismatch {
+                expected: 12,
+                actual: 13
+            })
+        ));
+        assert!(matches!(
+            YoloDecoder::yolov5(80).decode(&[0.5; 84], 0.25),
+            Err(Error::LengthMismatch {
+                expected: 0,
+                actual: 84
+            })
+        ));
+        assert!(YoloDecoder::yolov5(80)
+            .decode(&[], 0.25)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,321 @@
+//! Per-phase timing of the session lifecycle.
+//!
+//! A `Profiler` times named phases, such as image decoding, `add_input`, `run`
+//! and `get_output`, over many iterations. The first iterations are a warm-up
+//! and are not recorded. The report gives the percentiles of every phase and
+//! the throughput, as text or as JSON to compare runs across plug-in upgrades.
+//!
+//! ```rust, ignore
+//! let mut profiler = Profiler::new(10);
+//! for _ in 0..110 {
+//!     profiler.iteration(|p| {
+//!         let img = p.time("decode", || load_jpg_image_to_rgb32f(&img_buf, 224, 224));
+//!         p.time("add_input", || {
+//!             session.add_input("input", &img, &[1, 224, 224, 3]);
+//!         });
+//!         p.time("run", || {
+//!             session.run();
+//!         });
+//!         let res: Vec<f32> = p.time("get_output", || session.get_output("output"));
+//!     });
+//! }
+//! println!("{}", profiler.report());
+//! ```
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+pub struct Profiler {
+    warmup: usize,
+    // Iterations started so far, including the warm-up ones.
+    iterations: usize,
+    // Samples of every phase, in the order the phases were first timed.
+    phases: Vec<(String, Vec<Duration>)>,
+    // Duration of every recorded iteration.
+    totals: Vec<Duration>,
+}
+
+impl Profiler {
+    // Create a profiler that ignores the first `warmup` iterations.
+    pub fn new(warmup: usize) -> Profiler {
+        Profiler {
+            warmup,
+            iterations: 0,
+            phases: Vec::new(),
+            totals: Vec::new(),
+        }
+    }
+
+    // Whether the current iteration is past the warm-up.
+    fn recording(&self) -> bool {
+        self.iterations > self.warmup
+    }
+
+    // Record a sample of `phase` measured by the caller.
+    pub fn record(&mut self, phase: &str, duration: Duration) {
+        if !self.recording() {
+            return;
+        }
+        match self.phases.iter_mut().find(|(name, _)| name == phase) {
+            Some((_, samples)) => samples.push(duration),
+            None => self.phases.push((phase.to_string(), vec![duration])),
+        }
+    }
+
+    // Run `f` and record its duration as a sample of `phase`.
+    pub fn time<R, F: FnOnce() -> R>(&mut self, phase: &str, f: F) -> R {
+        let start = Instant::now();
+        let res = f();
+        self.record(phase, start.elapsed());
+        res
+    }
+
+    // Run one iteration, timing its phases with the profiler passed to `f`.
+    pub fn iteration<R, F: FnOnce(&mut Profiler) -> R>(&mut self, f: F) -> R {
+        self.iterations += 1;
+        let start = Instant::now();
+        let res = f(self);
+        if self.recording() {
+            self.totals.push(start.elapsed());
+        }
+        res
+    }
+
+    pub fn report(&self) -> Report {
+        Report {
+            warmup: self.warmup.min(self.iterations),
+            iterations: self.totals.len(),
+            total: Stats::new(&self.totals),
+            phases: self
+                .phases
+                .iter()
+                .map(|(name, samples)| (name.clone(), Stats::new(samples)))
+                .collect(),
+        }
+    }
+}
+
+// Summary of the samples of a phase.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub count: usize,
+    pub min: Duration,
+    pub mean: Duration,
+    pub max: Duration,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+}
+
+impl Stats {
+    // Compute the statistics of `samples`, with nearest-rank percentiles.
+    pub fn new(samples: &[Duration]) -> Stats {
+        if samples.is_empty() {
+            return Stats::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let percentile = |p: usize| sorted[(p * sorted.len()).div_ceil(100).max(1) - 1];
+        Stats {
+            count: sorted.len(),
+            min: sorted[0],
+            mean: sorted.iter().sum::<Duration>() / sorted.len() as u32,
+            max: sorted[sorted.len() - 1],
+            p50: percentile(50),
+            p95: percentile(95),
+            p99: percentile(99),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+    // Number of warm-up iterations that were not recorded.
+    pub warmup: usize,
+    // Number of recorded iterations.
+    pub iterations: usize,
+    // Duration of whole iterations.
+    pub total: Stats,
+    pub phases: Vec<(String, Stats)>,
+}
+
+impl Report {
+    // Iterations per second.
+    pub fn throughput(&self) -> f64 {
+        let secs = self.total.mean.as_secs_f64();
+        if secs > 0.0 {
+            1.0 / secs
+        } else {
+            0.0
+        }
+    }
+
+    // The report as a JSON object, with durations in milliseconds.
+    pub fn to_json(&self) -> String {
+        let phases: Vec<String> = self
+            .phases
+            .iter()
+            .map(|(name, stats)| format!("{}: {}", json_string(name), stats_json(stats)))
+            .collect();
+        format!(
+            "{{\"warmup\": {}, \"iterations\": {}, \"throughput\": {:.3}, \"total\": {}, \"phases\": {{{}}}}}",
+            self.warmup,
+            self.iterations,
+            self.throughput(),
+            stats_json(&self.total),
+            phases.join(", ")
+        )
+    }
+}
+
+fn ms(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+fn stats_json(s: &Stats) -> String {
+    format!(
+        "{{\"count\": {}, \"min_ms\": {:.3}, \"mean_ms\": {:.3}, \"max_ms\": {:.3}, \"p50_ms\": {:.3}, \"p95_ms\": {:.3}, \"p99_ms\": {:.3}}}",
+        s.count,
+        ms(s.min),
+        ms(s.mean),
+        ms(s.max),
+        ms(s.p50),
+        ms(s.p95),
+        ms(s.p99)
+    )
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} iterations after {} warm-up, {:.2} iterations/s",
+            self.iterations,
+            self.warmup,
+            self.throughput()
+        )?;
+        writeln!(
+            f,
+            "{:<12} {:>10} {:>10} {:>10} {:>10} {:>10}",
+            "phase (ms)", "mean", "p50", "p95", "p99", "max"
+        )?;
+        let rows = self.phases.iter().map(|(name, s)| (name.as_str(), s));
+        for (name, s) in rows.chain(std::iter::once(("total", &self.total))) {
+            writeln!(
+                f,
+                "{:<12} {:>10.3} {:>10.3} {:>10.3} {:>10.3} {:>10.3}",
+                name,
+                ms(s.mean),
+                ms(s.p50),
+                ms(s.p95),
+                ms(s.p99),
+                ms(s.max)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(ms: &[u64]) -> Vec<Duration> {
+        ms.iter().map(|ms| Duration::from_millis(*ms)).collect()
+    }
+
+    #[test]
+    fn nearest_rank_percentiles() {
+        // 1 to 100 ms, out of order.
+        let samples: Vec<u64> = (1..=100).map(|i| i * 37 % 101).collect();
+        let stats = Stats::new(&millis(&samples));
+        assert_eq!(stats.count, 100);
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.max, Duration::from_millis(100));
+        assert_eq!(stats.mean, Duration::from_micros(50_500));
+        assert_eq!(stats.p50, Duration::from_millis(50));
+        assert_eq!(stats.p95, Duration::from_millis(95));
+        assert_eq!(stats.p99, Duration::from_millis(99));
+
+        let stats = Stats::new(&millis(&[4, 1, 7]));
+        assert_eq!(stats.mean, Duration::from_millis(4));
+        assert_eq!(stats.p50, Duration::from_millis(4));
+        assert_eq!(stats.p95, Duration::from_millis(7));
+
+        let stats = Stats::new(&millis(&[3]));
+        assert_eq!(
+            (stats.min, stats.p50, stats.p99),
+            (stats.max, stats.max, stats.max)
+        );
+        assert_eq!(Stats::new(&[]), Stats::default());
+    }
+
+    #[test]
+    fn warmup_is_not_recorded() {
+        let mut profiler = Profiler::new(2);
+        for i in 1..=5 {
+            profiler.iteration(|p| {
+                p.record("decode", Duration::from_millis(i));
+                if i % 2 == 1 {
+                    p.record("run", Duration::from_millis(10 * i));
+                }
+            });
+        }
+        let report = profiler.report();
+        assert_eq!((report.warmup, report.iterations), (2, 3));
+        assert_eq!(report.total.count, 3);
+        let phases: Vec<_> = report
+            .phases
+            .iter()
+            .map(|(n, s)| (n.as_str(), s.count))
+            .collect();
+        assert_eq!(phases, [("decode", 3), ("run", 2)]);
+        assert_eq!(report.phases[0].1.min, Duration::from_millis(3));
+        assert_eq!(report.phases[1].1.mean, Duration::from_millis(40));
+
+        // Fewer iterations than the warm-up record nothing.
+        let mut profiler = Profiler::new(10);
+        profiler.iteration(|p| p.record("run", Duration::from_millis(1)));
+        let report = profiler.report();
+        assert_eq!((report.warmup, report.iterations), (1, 0));
+        assert!(report.phases.is_empty());
+        assert_eq!(report.throughput(), 0.0);
+    }
+
+    #[test]
+    fn report_json() {
+        let stats = Stats::new(&millis(&[2, 4]));
+        let report = Report {
+            warmup: 1,
+            iterations: 2,
+            total: stats,
+            phases: vec![("run \"fast\"".to_string(), stats)],
+        };
+        assert_eq!(report.throughput(), 1000.0 / 3.0);
+        let stats = "{\"count\": 2, \"min_ms\": 2.000, \"mean_ms\": 3.000, \"max_ms\": 4.000, \
+                     \"p50_ms\": 2.000, \"p95_ms\": 4.000, \"p99_ms\": 4.000}";
+        assert_eq!(
+            report.to_json(),
+            format!(
+                "{{\"warmup\": 1, \"iterations\": 2, \"throughput\": 333.333, \"total\": {}, \
+                 \"phases\": {{\"run \\\"fast\\\"\": {}}}}}",
+                stats, stats
+            )
+        );
+        assert_eq!(json_string("a\\b\n"), "\"a\\\\b\\u000a\"");
+    }
+}
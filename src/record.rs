@@ -0,0 +1,609 @@
+//! Recording of session runs, and their replay without the plug-ins.
+//!
+//! A `Recorder` wraps a session running in WasmEdge and writes the inputs of
+//! every run, with the outputs and shapes read after it, to a file. A `Replay`
+//! serves the recorded outputs back natively, for example under `cargo test`,
+//! so that the code around a model can be tested on any machine. Code written
+//! against the `Backend` trait runs unchanged on sessions, recorders and replays.
+//!
+//! ```rust, ignore
+//! fn classify<B: Backend>(session: &mut B, img: &[f32]) -> Vec<f32> {
+//!     session.add_input("input", img, &[1, 224, 224, 3]).run();
+//!     session.get_output("MobilenetV2/Predictions/Softmax")
+//! }
+//!
+//! // In WasmEdge, with the plug-in:
+//! let mut recorder = Recorder::create(TFLiteSession::new(&mod_buf), "mobilenet.rec").unwrap();
+//! classify(&mut recorder, &flat_img);
+//! recorder.finish().unwrap();
+//!
+//! // In a native test:
+//! let mut replay = Replay::open("tests/data/mobilenet.rec").unwrap();
+//! let res = classify(&mut replay, &flat_img);
+//! ```
+
+use crate::{error, DataType, TFLiteSession, TFSession, TensorType};
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"WETFREC\x01";
+
+// The session operations shared by the TensorFlow and TensorFlow-Lite sessions,
+// the recorder and the replay.
+pub trait Backend {
+    // Set an input tensor. TensorFlow-Lite sessions ignore the shape.
+    fn add_input<T: TensorType>(&mut self, name: &str, data: &[T], shape: &[u64]) -> &mut Self;
+    // Clear the inputs before setting new ones. TensorFlow sessions append every
+    // input they are given, while TensorFlow-Lite sessions overwrite them.
+    fn clear_input(&mut self) -> &mut Self;
+    // Request an output tensor. Only TensorFlow sessions need it.
+    fn add_output(&mut self, name: &str) -> &mut Self;
+    fn run(&mut self) -> &mut Self;
+    fn get_output<T: TensorType>(&self, name: &str) -> Vec<T>;
+    fn get_output_shape(&self, name: &str) -> error::Result<Vec<u64>>;
+}
+
+impl Backend for TFSession {
+    fn add_input<T: TensorType>(&mut self, name: &str, data: &[T], shape: &[u64]) -> &mut Self {
+        TFSession::add_input(self, name, data, shape)
+    }
+
+    fn clear_input(&mut self) -> &mut Self {
+        TFSession::clear_input(self)
+    }
+
+    fn add_output(&mut self, name: &str) -> &mut Self {
+        TFSession::add_output(self, name)
+    }
+
+    fn run(&mut self) -> &mut Self {
+        TFSession::run(self)
+    }
+
+    fn get_output<T: TensorType>(&self, name: &str) -> Vec<T> {
+        TFSession::get_output(self, name)
+    }
+
+    fn get_output_shape(&self, name: &str) -> error::Result<Vec<u64>> {
+        TFSession::get_output_shape(self, name)
+    }
+}
+
+impl Backend for TFLiteSession {
+    fn add_input<T: TensorType>(&mut self, name: &str, data: &[T], _shape: &[u64]) -> &mut Self {
+        TFLiteSession::add_input(self, name, data)
+    }
+
+    fn clear_input(&mut self) -> &mut Self {
+        self
+    }
+
+    fn add_output(&mut self, _name: &str) -> &mut Self {
+        self
+    }
+
+    fn run(&mut self) -> &mut Self {
+        TFLiteSession::run(self)
+    }
+
+    fn get_output<T: TensorType>(&self, name: &str) -> Vec<T> {
+        TFLiteSession::get_output(self, name)
+    }
+
+    fn get_output_shape(&self, name: &str) -> error::Result<Vec<u64>> {
+        TFLiteSession::get_output_shape(self, name)
+    }
+}
+
+// A recorded tensor. Outputs only have the data and the shape that were read.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Tensor {
+    name: String,
+    dtype: Option<DataType>,
+    shape: Option<Vec<u64>>,
+    data: Vec<u8>,
+}
+
+// The inputs of a run and the outputs read after it.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Run {
+    inputs: Vec<Tensor>,
+    outputs: Vec<Tensor>,
+}
+
+// Set the tensor `name`, keeping the tensors sorted by name.
+fn set_input(inputs: &mut Vec<Tensor>, tensor: Tensor) {
+    match inputs.binary_search_by(|t| t.name.cmp(&tensor.name)) {
+        Ok(i) => inputs[i] = tensor,
+        Err(i) => inputs.insert(i, tensor),
+    }
+}
+
+fn input<T: TensorType>(name: &str, data: &[T], shape: &[u64]) -> Tensor {
+    Tensor {
+        name: name.to_string(),
+        dtype: Some(T::DATA_TYPE),
+        shape: Some(shape.to_vec()),
+        data: T::as_bytes(data).to_vec(),
+    }
+}
+
+// The output `name` of `outputs`, added if missing.
+fn output<'a>(outputs: &'a mut Vec<Tensor>, name: &str) -> &'a mut Tensor {
+    let i = match outputs.iter().position(|t| t.name == name) {
+        Some(i) => i,
+        None => {
+            outputs.push(Tensor {
+                name: name.to_string(),
+                ..Tensor::default()
+            });
+            outputs.len() - 1
+        }
+    };
+    &mut outputs[i]
+}
+
+// A session wrapper that records every run to a file.
+pub struct Recorder<S> {
+    session: S,
+    file: BufWriter<File>,
+    // Inputs set so far, which the sessions keep across runs.
+    inputs: Vec<Tensor>,
+    // The last run, written once the next one starts or on `finish`.
+    last: RefCell<Option<Run>>,
+    error: Option<io::Error>,
+}
+
+impl<S> Recorder<S> {
+    // Record the runs of `session` to the file at `path`.
+    pub fn create<P: AsRef<Path>>(session: S, path: P) -> io::Result<Recorder<S>> {
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(MAGIC)?;
+        Ok(Recorder {
+            session,
+            file,
+            inputs: Vec::new(),
+            last: RefCell::new(None),
+            error: None,
+        })
+    }
+
+    pub fn session(&self) -> &S {
+        &self.session
+    }
+
+    // Write the last run and flush the file, returning the first write error.
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.write_last();
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => self.file.flush(),
+        }
+    }
+
+    // Write the pending run, keeping the first write error for `finish`.
+    fn write_last(&mut self) {
+        if let Some(run) = self.last.get_mut().take() {
+            if self.error.is_none() {
+                if let Err(e) = write_run(&mut self.file, &run) {
+                    self.error = Some(e);
+                }
+            }
+        }
+    }
+}
+
+impl<S: Backend> Backend for Recorder<S> {
+    fn add_input<T: TensorType>(&mut self, name: &str, data: &[T], shape: &[u64]) -> &mut Self {
+        self.session.add_input(name, data, shape);
+        set_input(&mut self.inputs, input(name, data, shape));
+        self
+    }
+
+    fn clear_input(&mut self) -> &mut Self {
+        self.session.clear_input();
+        self.inputs.clear();
+        self
+    }
+
+    fn add_output(&mut self, name: &str) -> &mut Self {
+        self.session.add_output(name);
+        self
+    }
+
+    fn run(&mut self) -> &mut Self {
+        self.write_last();
+        self.session.run();
+        *self.last.get_mut() = Some(Run {
+            inputs: self.inputs.clone(),
+            outputs: Vec::new(),
+        });
+        self
+    }
+
+    fn get_output<T: TensorType>(&self, name: &str) -> Vec<T> {
+        let data = self.session.get_output::<T>(name);
+        if let Some(run) = self.last.borrow_mut().as_mut() {
+            let tensor = output(&mut run.outputs, name);
+            tensor.dtype = Some(T::DATA_TYPE);
+            tensor.data = T::as_bytes(&data).to_vec();
+        }
+        data
+    }
+
+    fn get_output_shape(&self, name: &str) -> error::Result<Vec<u64>> {
+        let shape = self.session.get_output_shape(name)?;
+        if let Some(run) = self.last.borrow_mut().as_mut() {
+            output(&mut run.outputs, name).shape = Some(shape.clone());
+        }
+        Ok(shape)
+    }
+}
+
+// Dropping the recorder writes the last run, ignoring errors; call `finish` to
+// get them.
+impl<S> Drop for Recorder<S> {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+// A backend serving the outputs of recorded runs.
+//
+// By default a run replays the first recorded run with the same inputs, and
+// panics when there is none. With `in_order`, runs replay the recorded runs one
+// after the other whatever the inputs.
+pub struct Replay {
+    runs: Vec<Run>,
+    in_order: bool,
+    inputs: Vec<Tensor>,
+    // Index of the next run replayed in order.
+    next: usize,
+    current: Option<usize>,
+}
+
+impl Replay {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Replay> {
+        let mut buf = Vec::new();
+        File::open(path)?.read_to_end(&mut buf)?;
+        Replay::from_bytes(&buf)
+    }
+
+    // Parse the content of a recording file.
+    pub fn from_bytes(buf: &[u8]) -> io::Result<Replay> {
+        let mut reader = Reader { buf, pos: 0 };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(invalid("not a session recording"));
+        }
+        let mut runs = Vec::new();
+        while reader.pos < buf.len() {
+            runs.push(reader.run()?);
+        }
+        Ok(Replay {
+            runs,
+            in_order: false,
+            inputs: Vec::new(),
+            next: 0,
+            current: None,
+        })
+    }
+
+    // Replay the recorded runs in order, without matching the inputs.
+    pub fn in_order(mut self) -> Replay {
+        self.in_order = true;
+        self
+    }
+
+    // Number of recorded runs.
+    pub fn len(&self) -> usize {
+        self.runs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    // The output `name` of the current run.
+    fn output(&self, name: &str) -> &Tensor {
+        let run = self.current.expect("the replay has not been run");
+        self.runs[run]
+            .outputs
+            .iter()
+            .find(|t| t.name == name)
+            .unwrap_or_else(|| panic!("output `{}` was not recorded in run {}", name, run))
+    }
+}
+
+impl Backend for Replay {
+    fn add_input<T: TensorType>(&mut self, name: &str, data: &[T], shape: &[u64]) -> &mut Self {
+        set_input(&mut self.inputs, input(name, data, shape));
+        self
+    }
+
+    fn clear_input(&mut self) -> &mut Self {
+        self.inputs.clear();
+        self
+    }
+
+    fn add_output(&mut self, _name: &str) -> &mut Self {
+        self
+    }
+
+    fn run(&mut self) -> &mut Self {
+        let run = if self.in_order {
+            let run = self.next;
+            assert!(
+                run < self.runs.len(),
+                "all {} recorded runs have been replayed",
+                self.runs.len()
+            );
+            self.next += 1;
+            run
+        } else {
+            let inputs = &self.inputs;
+            self.runs
+                .iter()
+                .position(|run| &run.inputs == inputs)
+                .unwrap_or_else(|| {
+                    let inputs: Vec<String> = inputs
+                        .iter()
+                        .map(|t| format!("{} {:?}", t.name, t.shape.as_deref().unwrap_or(&[])))
+                        .collect();
+                    panic!("no recorded run has the inputs {}", inputs.join(", "))
+                })
+        };
+        self.current = Some(run);
+        self
+    }
+
+    fn get_output<T: TensorType>(&self, name: &str) -> Vec<T> {
+        let tensor = self.output(name);
+        match tensor.dtype {
+            Some(dtype) if dtype == T::DATA_TYPE => T::from_bytes(&tensor.data),
+            Some(dtype) => panic!(
+                "output `{}` was recorded as {}, not {}",
+                name,
+                dtype,
+                T::DATA_TYPE
+            ),
+            None => panic!("the data of output `{}` was not recorded", name),
+        }
+    }
+
+    fn get_output_shape(&self, name: &str) -> error::Result<Vec<u64>> {
+        Ok(self
+            .output(name)
+            .shape
+            .clone()
+            .unwrap_or_else(|| panic!("the shape of output `{}` was not recorded", name)))
+    }
+}
+
+// File format, after the magic string, with little-endian integers:
+//
+//     run    := u32 input count, tensor*, u32 output count, tensor*
+//     tensor := u32 name length, name, u32 DataType value or 0,
+//               u32 rank or u32::MAX without shape, u64 dimension*,
+//               u64 data length, data
+fn write_run<W: Write>(w: &mut W, run: &Run) -> io::Result<()> {
+    for tensors in [&run.inputs, &run.outputs] {
+        w.write_all(&(tensors.len() as u32).to_le_bytes())?;
+        for t in tensors {
+            w.write_all(&(t.name.len() as u32).to_le_bytes())?;
+            w.write_all(t.name.as_bytes())?;
+            w.write_all(&t.dtype.map_or(0, DataType::value).to_le_bytes())?;
+            match &t.shape {
+                Some(shape) => {
+                    w.write_all(&(shape.len() as u32).to_le_bytes())?;
+                    for d in shape {
+                        w.write_all(&d.to_le_bytes())?;
+                    }
+                }
+                None => w.write_all(&u32::MAX.to_le_bytes())?,
+            }
+            w.write_all(&(t.data.len() as u64).to_le_bytes())?;
+            w.write_all(&t.data)?;
+        }
+    }
+    Ok(())
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.buf.len())
+            .ok_or_else(|| invalid("truncated session recording"))?;
+        let bytes = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn size(&mut self, value: u64) -> io::Result<usize> {
+        usize::try_from(value).map_err(|_| invalid("session recording too large"))
+    }
+
+    fn tensor(&mut self) -> io::Result<Tensor> {
+        let name_len = self.u32()? as usize;
+        let name = String::from_utf8_lossy(self.take(name_len)?).into_owned();
+        let dtype = match self.u32()? {
+            0 => None,
+            v => Some(DataType::from_value(v).ok_or_else(|| invalid("unknown tensor type"))?),
+        };
+        let shape = match self.u32()? {
+            u32::MAX => None,
+            rank => Some((0..rank).map(|_| self.u64()).collect::<io::Result<_>>()?),
+        };
+        let data_len = self.u64()?;
+        let data_len = self.size(data_len)?;
+        let data = self.take(data_len)?.to_vec();
+        Ok(Tensor {
+            name,
+            dtype,
+            shape,
+            data,
+        })
+    }
+
+    fn run(&mut self) -> io::Result<Run> {
+        let mut run = Run::default();
+        for tensors in [&mut run.inputs, &mut run.outputs] {
+            let count = self.u32()?;
+            for _ in 0..count {
+                tensors.push(self.tensor()?);
+            }
+        }
+        Ok(run)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    // A model doubling its `f32` input `x` into the output `y`.
+    #[derive(Default)]
+    struct Doubler {
+        x: Vec<f32>,
+        y: Vec<f32>,
+    }
+
+    impl Backend for Doubler {
+        fn add_input<T: TensorType>(
+            &mut self,
+            _name: &str,
+            data: &[T],
+            _shape: &[u64],
+        ) -> &mut Self {
+            self.x = f32::from_bytes(T::as_bytes(data));
+            self
+        }
+
+        fn clear_input(&mut self) -> &mut Self {
+            self.x.clear();
+            self
+        }
+
+        fn add_output(&mut self, _name: &str) -> &mut Self {
+            self
+        }
+
+        fn run(&mut self) -> &mut Self {
+            self.y = self.x.iter().map(|x| x * 2.0).collect();
+            self
+        }
+
+        fn get_output<T: TensorType>(&self, _name: &str) -> Vec<T> {
+            T::from_bytes(f32::as_bytes(&self.y))
+        }
+
+        fn get_output_shape(&self, _name: &str) -> error::Result<Vec<u64>> {
+            Ok(vec![1, self.y.len() as u64])
+        }
+    }
+
+    fn infer<B: Backend>(session: &mut B, x: &[f32]) -> Vec<f32> {
+        session.add_input("x", x, &[1, x.len() as u64]).run();
+        session.get_output("y")
+    }
+
+    // Record runs on `[1, 2]` with the output shape, then on `[3]`.
+    fn record(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}.rec", name, std::process::id()));
+        let mut recorder = Recorder::create(Doubler::default(), &path).unwrap();
+        assert_eq!(infer(&mut recorder, &[1.0, 2.0]), [2.0, 4.0]);
+        assert_eq!(recorder.get_output_shape("y").unwrap(), [1, 2]);
+        assert_eq!(infer(&mut recorder, &[3.0]), [6.0]);
+        recorder.finish().unwrap();
+        path
+    }
+
+    fn replay(name: &str) -> Replay {
+        let path = record(name);
+        let replay = Replay::open(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        replay
+    }
+
+    #[test]
+    fn replays_runs_with_the_same_inputs() {
+        let mut replay = replay("record-inputs");
+        assert_eq!(replay.len(), 2);
+        assert_eq!(infer(&mut replay, &[3.0]), [6.0]);
+        assert_eq!(infer(&mut replay, &[1.0, 2.0]), [2.0, 4.0]);
+        assert_eq!(replay.get_output_shape("y").unwrap(), [1, 2]);
+        // Clearing the inputs does not change which run matches.
+        replay.clear_input();
+        assert_eq!(infer(&mut replay, &[3.0]), [6.0]);
+    }
+
+    #[test]
+    fn replays_runs_in_order() {
+        let mut replay = replay("record-in-order").in_order();
+        assert_eq!(infer(&mut replay, &[0.0]), [2.0, 4.0]);
+        assert_eq!(infer(&mut replay, &[0.0]), [6.0]);
+        let end = std::panic::catch_unwind(move || infer(&mut replay, &[0.0])).unwrap_err();
+        assert_eq!(
+            end.downcast_ref::<String>().unwrap(),
+            "all 2 recorded runs have been replayed"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "no recorded run has the inputs x [1, 1]")]
+    fn other_inputs_do_not_match() {
+        infer(&mut replay("record-mismatch"), &[4.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "output `y` was recorded as float32, not int32")]
+    fn outputs_keep_their_type() {
+        let mut replay = replay("record-type");
+        infer(&mut replay, &[3.0]);
+        replay.get_output::<i32>("y");
+    }
+
+    #[test]
+    #[should_panic(expected = "the shape of output `y` was not recorded")]
+    fn only_read_shapes_are_recorded() {
+        let mut replay = replay("record-shape");
+        infer(&mut replay, &[3.0]);
+        let _ = replay.get_output_shape("y");
+    }
+
+    #[test]
+    fn malformed_recordings_are_errors() {
+        let path = record("record-malformed");
+        let buf = std::fs::read(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(Replay::from_bytes(&buf).unwrap().len(), 2);
+        assert!(Replay::from_bytes(&buf[..buf.len() - 1]).is_err());
+        assert!(Replay::from_bytes(&buf[..MAGIC.len() + 2]).is_err());
+        assert!(Replay::from_bytes(b"WETFREC\x02").is_err());
+        // An input count past the end of the recording.
+        let mut bad = buf;
+        bad[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(Replay::from_bytes(&bad).is_err());
+    }
+}
@@ -0,0 +1,82 @@
+//! Host ABI encoding of `TF_STRING` tensors.
+//!
+//! A string tensor is passed to and read from the host as one flat buffer holding
+//! every element in row-major order, each encoded as its byte length (`u32`,
+//! little endian) followed by its bytes, without padding. Elements are arbitrary
+//! bytes, such as serialized protos or encoded images; the string variants are
+//! for elements holding UTF-8 text.
+//!
+//! This is the `string_tensor_data` type of `witx/wasmedge_tensorflow.witx`,
+//! passed with the `append_string_input` and `get_string_tensor_data` host
+//! functions of the `extended-abi` feature.
+
+// Encode byte string elements into the host buffer layout.
+pub fn encode_bytes<B: AsRef<[u8]>>(elements: &[B]) -> Vec<u8> {
+    let len = elements.iter().map(|b| 4 + b.as_ref().len()).sum();
+    let mut buf = Vec::with_capacity(len);
+    for b in elements {
+        let bytes = b.as_ref();
+        buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        buf.extend_from_slice(bytes);
+    }
+    buf
+}
+
+// Encode string elements into the host buffer layout.
+pub fn encode_strings<S: AsRef<str>>(strings: &[S]) -> Vec<u8> {
+    let bytes: Vec<&[u8]> = strings.iter().map(|s| s.as_ref().as_bytes()).collect();
+    encode_bytes(&bytes)
+}
+
+// Decode a host buffer into byte string elements. A truncated trailing element
+// is ignored.
+pub fn decode_bytes(mut buf: &[u8]) -> Vec<Vec<u8>> {
+    let mut elements = Vec::new();
+    while buf.len() >= 4 {
+        let len = u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize;
+        let Some(end) = len.checked_add(4) else {
+            break;
+        };
+        let Some(bytes) = buf.get(4..end) else {
+            break;
+        };
+        elements.push(bytes.to_vec());
+        buf = &buf[end..];
+    }
+    elements
+}
+
+// Decode a host buffer into string elements. Invalid UTF-8 sequences are
+// replaced, so use `decode_bytes` for binary elements.
+pub fn decode_strings(buf: &[u8]) -> Vec<String> {
+    decode_bytes(buf)
+        .into_iter()
+        .map(|bytes| match String::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_round_trip() {
+        let elements: [&[u8]; 3] = [b"\x08\x96\x01", b"", b"\xff\xd8\xff\xe0"];
+        assert_eq!(decode_bytes(&encode_bytes(&elements)), elements);
+        assert_eq!(
+            decode_strings(&encode_strings(&["hello", "world"])),
+            ["hello", "world"]
+        );
+    }
+
+    #[test]
+    fn bad_lengths_stop_decoding() {
+        let mut buf = encode_strings(&["ok"]);
+        buf.extend_from_slice(&u32::MAX.to_le_bytes());
+        buf.extend_from_slice(b"abc");
+        assert_eq!(decode_strings(&buf), ["ok"]);
+    }
+}
@@ -0,0 +1,350 @@
+//! Golden-output comparisons for regression tests.
+//!
+//! Compare the outputs of a session with stored expected outputs, such as
+//! `.npy` files computed in Python, using the tolerance of `numpy.allclose`:
+//! an element is close when `|actual - expected| <= atol + rtol * |expected|`.
+//! Failed assertions report the worst elements along with the cosine
+//! similarity of the tensors. Elements of any real type are compared as `f64`,
+//! and are located by their index in the shape of the tensor when it is known.
+//!
+//! ```rust, ignore
+//! let res: Vec<f32> = session.get_output("MobilenetV2/Predictions/Softmax");
+//! testing::assert_npy_close(&res, "tests/golden/mobilenet_v2.npy", 1e-5, 1e-6);
+//!
+//! // Looser checks for outputs of quantized models.
+//! let expected: Vec<f32> = npy::read("tests/golden/mobilenet_v2.npy").unwrap().to_vec().unwrap();
+//! assert!(testing::cosine_similarity(&res, &expected) > 0.99);
+//! assert_eq!(testing::top_k_agreement(&res, &expected, 5), 1.0);
+//! ```
+
+use crate::convert::ToF64;
+use crate::npy;
+use crate::postprocess::classification::top_k;
+use std::fmt;
+use std::path::Path;
+
+// Number of elements listed in a comparison report.
+const REPORTED: usize = 10;
+
+// An element that is not within the tolerance.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Mismatch {
+    // Index of the element in the flat tensor.
+    pub index: usize,
+    pub actual: f64,
+    pub expected: f64,
+}
+
+impl Mismatch {
+    pub fn abs_diff(&self) -> f64 {
+        (self.actual - self.expected).abs()
+    }
+}
+
+// The result of comparing two tensors element by element.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comparison {
+    pub len: usize,
+    pub rtol: f64,
+    pub atol: f64,
+    // Number of elements that are not within the tolerance, NaN included.
+    pub mismatches: usize,
+    // The mismatches with the largest differences, worst first.
+    pub worst: Vec<Mismatch>,
+    // Largest absolute difference and its index.
+    pub max_abs_diff: f64,
+    pub max_abs_index: usize,
+    pub cosine_similarity: f64,
+    // Shape of the tensors, used to report indices: `[len]` unless set with
+    // `with_shape`.
+    pub shape: Vec<u64>,
+}
+
+impl Comparison {
+    pub fn is_close(&self) -> bool {
+        self.mismatches == 0
+    }
+
+    // Report indices in the tensor of the given shape.
+    // Panics if the shape does not hold `len` elements.
+    pub fn with_shape(mut self, shape: &[u64]) -> Comparison {
+        let count = shape.iter().try_fold(1u64, |n, d| n.checked_mul(*d));
+        assert_eq!(
+            count,
+            Some(self.len as u64),
+            "shape {:?} does not hold {} elements",
+            shape,
+            self.len
+        );
+        self.shape = shape.to_vec();
+        self
+    }
+
+    // The index in each dimension of the element at `index` of the flat
+    // tensor, in C order.
+    pub fn unravel(&self, index: usize) -> Vec<u64> {
+        let mut rest = index as u64;
+        let mut indices = vec![0; self.shape.len()];
+        for (i, d) in self.shape.iter().enumerate().rev() {
+            if *d > 0 {
+                indices[i] = rest % d;
+                rest /= d;
+            }
+        }
+        indices
+    }
+
+    fn format_index(&self, index: usize) -> String {
+        let indices: Vec<String> = self.unravel(index).iter().map(u64::to_string).collect();
+        format!("[{}]", indices.join(", "))
+    }
+}
+
+fn to_f64<T: ToF64>(values: &[T]) -> impl Iterator<Item = f64> + '_ {
+    values.iter().map(|v| v.to_f64())
+}
+
+// Compare `actual` with `expected` element by element.
+// Panics if the tensors do not have the same length.
+pub fn compare<T: ToF64>(actual: &[T], expected: &[T], rtol: f64, atol: f64) -> Comparison {
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "tensor length mismatch: actual has {} elements, expected {}",
+        actual.len(),
+        expected.len()
+    );
+    let mut mismatches = Vec::new();
+    let (max_abs_index, max_abs_diff) = max_abs_diff(actual, expected).unwrap_or((0, 0.0));
+    for (index, (a, e)) in to_f64(actual).zip(to_f64(expected)).enumerate() {
+        // NaN is never close, like in `numpy.allclose` by default.
+        let close = a == e || (a - e).abs() <= atol + rtol * e.abs();
+        if !close {
+            mismatches.push(Mismatch {
+                index,
+                actual: a,
+                expected: e,
+            });
+        }
+    }
+    let count = mismatches.len();
+    // Stable sort, so equal differences keep the lower index first.
+    mismatches.sort_by(|x, y| y.abs_diff().total_cmp(&x.abs_diff()));
+    mismatches.truncate(REPORTED);
+    Comparison {
+        len: actual.len(),
+        rtol,
+        atol,
+        mismatches: count,
+        worst: mismatches,
+        max_abs_diff,
+        max_abs_index,
+        cosine_similarity: cosine_similarity(actual, expected),
+        shape: vec![actual.len() as u64],
+    }
+}
+
+// Panic with a report of the worst elements unless every element of `actual`
+// is within the tolerance of `expected`.
+#[track_caller]
+pub fn assert_tensor_close<T: ToF64>(actual: &[T], expected: &[T], rtol: f64, atol: f64) {
+    let cmp = compare(actual, expected, rtol, atol);
+    if !cmp.is_close() {
+        panic!("tensors are not close: {}", cmp);
+    }
+}
+
+// Compare `actual` with the expected tensor stored in a `.npy` file, which
+// must hold elements of the same type. Mismatches are reported by their index
+// in the shape of the stored tensor.
+#[track_caller]
+pub fn assert_npy_close<T, P>(actual: &[T], path: P, rtol: f64, atol: f64)
+where
+    T: ToF64,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let array = npy::read(path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    let expected = array
+        .to_vec::<T>()
+        .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    let cmp = compare(actual, &expected, rtol, atol).with_shape(array.shape());
+    if !cmp.is_close() {
+        panic!("tensor is not close to {}: {}", path.display(), cmp);
+    }
+}
+
+// Index and value of the largest absolute difference, or `None` for empty
+// tensors. A NaN difference is the largest.
+pub fn max_abs_diff<T: ToF64>(actual: &[T], expected: &[T]) -> Option<(usize, f64)> {
+    to_f64(actual)
+        .zip(to_f64(expected))
+        .map(|(a, e)| (a - e).abs())
+        .enumerate()
+        .reduce(|max, d| {
+            if d.1.total_cmp(&max.1).is_gt() {
+                d
+            } else {
+                max
+            }
+        })
+}
+
+// Cosine of the angle between the tensors as vectors: 1 for tensors pointing
+// the same way, whatever their scale. Two zero tensors have a similarity of 1.
+pub fn cosine_similarity<T: ToF64>(actual: &[T], expected: &[T]) -> f64 {
+    let (mut dot, mut norm_a, mut norm_e) = (0.0, 0.0, 0.0);
+    for (a, e) in to_f64(actual).zip(to_f64(expected)) {
+        dot += a * e;
+        norm_a += a * a;
+        norm_e += e * e;
+    }
+    if norm_a == 0.0 && norm_e == 0.0 {
+        1.0
+    } else {
+        dot / (norm_a.sqrt() * norm_e.sqrt())
+    }
+}
+
+// Fraction of the `k` best classes of `expected` that are among the `k` best
+// classes of `actual`, in any order.
+pub fn top_k_agreement<T: ToF64>(actual: &[T], expected: &[T], k: usize) -> f64 {
+    let scores = |values: &[T]| -> Vec<f32> { to_f64(values).map(|v| v as f32).collect() };
+    let actual_top = top_k(&scores(actual), k);
+    let expected_top = top_k(&scores(expected), k);
+    if expected_top.is_empty() {
+        return 1.0;
+    }
+    let common = expected_top
+        .iter()
+        .filter(|(i, _)| actual_top.iter().any(|(j, _)| i == j))
+        .count();
+    common as f64 / expected_top.len() as f64
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} of {} elements differ (rtol {:e}, atol {:e})",
+            self.mismatches, self.len, self.rtol, self.atol
+        )?;
+        writeln!(
+            f,
+            "max abs diff {:e} at {}, cosine similarity {:.6}",
+            self.max_abs_diff,
+            self.format_index(self.max_abs_index),
+            self.cosine_similarity
+        )?;
+        if !self.worst.is_empty() {
+            writeln!(
+                f,
+                "{:>10} {:>14} {:>14} {:>12}",
+                "index", "actual", "expected", "abs diff"
+            )?;
+        }
+        for m in &self.worst {
+            writeln!(
+                f,
+                "{:>10} {:>14.6e} {:>14.6e} {:>12.3e}",
+                self.format_index(m.index),
+                m.actual,
+                m.expected,
+                m.abs_diff()
+            )?;
+        }
+        if self.mismatches > self.worst.len() {
+            writeln!(f, "... and {} more", self.mismatches - self.worst.len())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allclose_tolerance() {
+        let expected = [1.0f32, 100.0, 0.0, f32::NAN];
+        let actual = [1.05f32, 100.5, 0.0, f32::NAN];
+        let cmp = compare(&actual, &expected, 1e-2, 0.1);
+        // NaN is never close, even to NaN.
+        assert_eq!(cmp.mismatches, 1);
+        assert_eq!(cmp.worst[0].index, 3);
+        assert!(compare(&actual[..3], &expected[..3], 1e-2, 0.1).is_close());
+        assert!(!compare(&actual[..3], &expected[..3], 0.0, 0.01).is_close());
+        assert_tensor_close(&actual[..3], &expected[..3], 1e-2, 0.1);
+    }
+
+    #[test]
+    fn integer_elements() {
+        // 64-bit integers, which do not convert to `f64` with `Into`.
+        let expected = [i64::MIN, -3, 1 << 40];
+        let cmp = compare(&[i64::MIN, -1, 1 << 40], &expected, 0.0, 1.0);
+        assert_eq!(cmp.mismatches, 1);
+        assert_eq!(cmp.worst[0].actual, -1.0);
+        assert_eq!(cmp.worst[0].expected, -3.0);
+        assert_eq!(
+            max_abs_diff(&[0u64, u64::MAX], &[0, 0]),
+            Some((1, u64::MAX as f64))
+        );
+        assert!(compare(&[true, false], &[true, false], 0.0, 0.0).is_close());
+        assert_eq!(top_k_agreement(&[1u8, 9, 5], &[2u8, 8, 4], 2), 1.0);
+    }
+
+    #[test]
+    fn worst_mismatches_first() {
+        let expected = vec![0.0f64; 20];
+        let actual: Vec<f64> = (0..20).map(|i| i as f64).collect();
+        let cmp = compare(&actual, &expected, 0.0, 0.5);
+        assert_eq!(cmp.mismatches, 19);
+        assert_eq!(cmp.worst.len(), REPORTED);
+        assert_eq!(cmp.worst[0].index, 19);
+        assert_eq!((cmp.max_abs_index, cmp.max_abs_diff), (19, 19.0));
+        assert!(cmp.to_string().contains("... and 9 more"));
+    }
+
+    #[test]
+    fn indices_in_the_shape() {
+        let expected = [0i32; 24];
+        let mut actual = expected;
+        actual[17] = 5;
+        let cmp = compare(&actual, &expected, 0.0, 0.0).with_shape(&[2, 3, 4]);
+        assert_eq!(cmp.unravel(17), [1, 1, 1]);
+        assert_eq!(cmp.unravel(23), [1, 2, 3]);
+        let report = cmp.to_string();
+        assert!(report.contains("at [1, 1, 1]"), "{}", report);
+        // Without a shape, indices are flat.
+        let cmp = compare(&actual, &expected, 0.0, 0.0);
+        assert_eq!(cmp.unravel(17), [17]);
+        assert!(cmp.to_string().contains("at [17]"));
+    }
+
+    #[test]
+    #[should_panic(expected = "does not hold 24 elements")]
+    fn shape_must_hold_the_elements() {
+        compare(&[0u8; 24], &[0u8; 24], 0.0, 0.0).with_shape(&[5, 5]);
+    }
+
+    #[test]
+    fn cosine_similarity_ignores_scale() {
+        assert!((cosine_similarity(&[1.0f32, 2.0], &[2.0, 4.0]) - 1.0).abs() < 1e-12);
+        assert!(cosine_similarity(&[1.0f32, 0.0], &[0.0, 1.0]).abs() < 1e-12);
+        assert_eq!(cosine_similarity(&[0i16; 3], &[0i16; 3]), 1.0);
+    }
+
+    #[test]
+    fn npy_golden_file() {
+        let path = std::env::temp_dir().join(format!("testing-{}.npy", std::process::id()));
+        npy::write(&path, &[1.0f32, 2.0, 3.0, 4.0], &[2, 2]).unwrap();
+        assert_npy_close(&[1.0f32, 2.0, 3.0, 4.0 + 1e-7], &path, 1e-5, 0.0);
+        let report = std::panic::catch_unwind(|| {
+            assert_npy_close(&[1.0f32, 2.0, 3.0, 5.0], &path, 1e-5, 0.0);
+        })
+        .unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        let report = report.downcast::<String>().unwrap();
+        assert!(report.contains("at [1, 1]"), "{}", report);
+    }
+}
@@ -0,0 +1,442 @@
+//! Tensor element types.
+//!
+//! `DataType` mirrors the `TF_DataType` values used by the host functions, and
+//! `TensorType` maps the supported Rust element types onto them. `TensorType` is
+//! sealed: only types whose in-memory layout matches their `DataType` implement it,
+//! which is what makes the byte-level conversions below safe.
+
+use crate::error::{Error, Result as TensorResult};
+use std::convert::Infallible;
+use std::fmt;
+use std::mem;
+
+// The TensorFlow data types, with their `TF_DataType` values.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[repr(u32)]
+pub enum DataType {
+    Float = 1,
+    Double = 2,
+    Int32 = 3,
+    UInt8 = 4,
+    Int16 = 5,
+    Int8 = 6,
+    String = 7,
+    Complex64 = 8,
+    Int64 = 9,
+    Bool = 10,
+    QInt8 = 11,
+    QUInt8 = 12,
+    QInt32 = 13,
+    BFloat16 = 14,
+    QInt16 = 15,
+    QUInt16 = 16,
+    UInt16 = 17,
+    Complex128 = 18,
+    Half = 19,
+    Resource = 20,
+    Variant = 21,
+    UInt32 = 22,
+    UInt64 = 23,
+}
+
+impl DataType {
+    // The `TF_DataType` value passed to the host.
+    pub const fn value(self) -> u32 {
+        self as u32
+    }
+
+    // Look up a `TF_DataType` value reported by the host.
+    pub const fn from_value(value: u32) -> Option<DataType> {
+        Some(match value {
+            1 => DataType::Float,
+            2 => DataType::Double,
+            3 => DataType::Int32,
+            4 => DataType::UInt8,
+            5 => DataType::Int16,
+            6 => DataType::Int8,
+            7 => DataType::String,
+            8 => DataType::Complex64,
+            9 => DataType::Int64,
+            10 => DataType::Bool,
+            11 => DataType::QInt8,
+            12 => DataType::QUInt8,
+            13 => DataType::QInt32,
+            14 => DataType::BFloat16,
+            15 => DataType::QInt16,
+            16 => DataType::QUInt16,
+            17 => DataType::UInt16,
+            18 => DataType::Complex128,
+            19 => DataType::Half,
+            20 => DataType::Resource,
+            21 => DataType::Variant,
+            22 => DataType::UInt32,
+            23 => DataType::UInt64,
+            _ => return None,
+        })
+    }
+
+    // Size in bytes of one element, or `None` for variable-sized types.
+    pub const fn size_of(self) -> Option<usize> {
+        match self {
+            DataType::Int8
+            | DataType::UInt8
+            | DataType::Bool
+            | DataType::QInt8
+            | DataType::QUInt8 => Some(1),
+            DataType::Int16
+            | DataType::UInt16
+            | DataType::QInt16
+            | DataType::QUInt16
+            | DataType::Half
+            | DataType::BFloat16 => Some(2),
+            DataType::Float | DataType::Int32 | DataType::UInt32 | DataType::QInt32 => Some(4),
+            DataType::Double | DataType::Int64 | DataType::UInt64 | DataType::Complex64 => Some(8),
+            DataType::Complex128 => Some(16),
+            DataType::String | DataType::Resource | DataType::Variant => None,
+        }
+    }
+
+    // The TensorFlow name of the type, such as `float32`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            DataType::Float => "float32",
+            DataType::Double => "float64",
+            DataType::Int32 => "int32",
+            DataType::UInt8 => "uint8",
+            DataType::Int16 => "int16",
+            DataType::Int8 => "int8",
+            DataType::String => "string",
+            DataType::Complex64 => "complex64",
+            DataType::Int64 => "int64",
+            DataType::Bool => "bool",
+            DataType::QInt8 => "qint8",
+            DataType::QUInt8 => "quint8",
+            DataType::QInt32 => "qint32",
+            DataType::BFloat16 => "bfloat16",
+            DataType::QInt16 => "qint16",
+            DataType::QUInt16 => "quint16",
+            DataType::UInt16 => "uint16",
+            DataType::Complex128 => "complex128",
+            DataType::Half => "float16",
+            DataType::Resource => "resource",
+            DataType::Variant => "variant",
+            DataType::UInt32 => "uint32",
+            DataType::UInt64 => "uint64",
+        }
+    }
+}
+
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl TryFrom<u32> for DataType {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<DataType, u32> {
+        DataType::from_value(value).ok_or(value)
+    }
+}
+
+mod sealed {
+    pub trait Sealed: Sized {
+        // Mutable byte view of an element buffer, for types where every bit
+        // pattern is a valid value.
+        fn bytes_mut(values: &mut [Self]) -> Option<&mut [u8]>;
+    }
+}
+
+pub(crate) use sealed::Sealed;
+
+// Rust element types of tensors.
+pub trait TensorType: sealed::Sealed + Copy + Default + 'static {
+    const DATA_TYPE: DataType;
+
+    // View an element buffer as the bytes passed to the host.
+    fn as_bytes(values: &[Self]) -> &[u8] {
+        // Safety: sealed implementors are plain data without padding.
+        unsafe { std::slice::from_raw_parts(values.as_ptr().cast(), mem::size_of_val(values)) }
+    }
+
+    // Copy elements out of host bytes. Trailing bytes that do not form a whole
+    // element are ignored.
+    fn from_bytes(bytes: &[u8]) -> Vec<Self>;
+}
+
+// Macro for mapping rust types onto tensor type. Internal only.
+macro_rules! tensor_type {
+    ($rust_type:ty, $data_type:ident) => {
+        const _: () = assert!(matches!(
+            DataType::$data_type.size_of(),
+            Some(n) if n == mem::size_of::<$rust_type>()
+        ));
+
+        impl Sealed for $rust_type {
+            fn bytes_mut(values: &mut [Self]) -> Option<&mut [u8]> {
+                // Safety: every bit pattern is a valid value of the type.
+                unsafe {
+                    Some(std::slice::from_raw_parts_mut(
+                        values.as_mut_ptr().cast(),
+                        mem::size_of_val(values),
+                    ))
+                }
+            }
+        }
+
+        impl TensorType for $rust_type {
+            const DATA_TYPE: DataType = DataType::$data_type;
+
+            fn from_bytes(bytes: &[u8]) -> Vec<Self> {
+                bytes
+                    .chunks_exact(mem::size_of::<Self>())
+                    // Safety: every bit pattern is a valid value of the type.
+                    .map(|c| unsafe { std::ptr::read_unaligned(c.as_ptr().cast()) })
+                    .collect()
+            }
+        }
+    };
+}
+tensor_type!(f32, Float);
+tensor_type!(f64, Double);
+tensor_type!(i32, Int32);
+tensor_type!(u8, UInt8);
+tensor_type!(u16, UInt16);
+tensor_type!(u32, UInt32);
+tensor_type!(u64, UInt64);
+tensor_type!(i16, Int16);
+tensor_type!(i8, Int8);
+tensor_type!(i64, Int64);
+#[cfg(feature = "half")]
+tensor_type!(half::bf16, BFloat16);
+#[cfg(feature = "half")]
+tensor_type!(half::f16, Half);
+#[cfg(feature = "num-complex")]
+tensor_type!(num_complex::Complex32, Complex64);
+#[cfg(feature = "num-complex")]
+tensor_type!(num_complex::Complex64, Complex128);
+
+// Only 0 and 1 are valid `bool` bytes, so host bytes are never viewed as `bool`.
+impl Sealed for bool {
+    fn bytes_mut(_values: &mut [Self]) -> Option<&mut [u8]> {
+        None
+    }
+}
+
+impl TensorType for bool {
+    const DATA_TYPE: DataType = DataType::Bool;
+
+    fn from_bytes(bytes: &[u8]) -> Vec<Self> {
+        bytes.iter().map(|b| *b != 0).collect()
+    }
+}
+
+// Read a tensor of `len` bytes written by the host through `read(buf, buf_max_size)`.
+pub(crate) fn read_tensor<T: TensorType>(len: usize, read: impl FnOnce(*mut u8, u32)) -> Vec<T> {
+    if len == 0 {
+        return Vec::new();
+    }
+    let mut data: Vec<T> = vec![T::default(); len / mem::size_of::<T>()];
+    let read = |buf, buf_max_size| {
+        read(buf, buf_max_size);
+        Ok::<(), Infallible>(())
+    };
+    let Ok(()) = read_tensor_into(&mut data, read);
+    data
+}
+
+// Fill `data` with the bytes written by the host through `read(buf, buf_max_size)`.
+pub(crate) fn read_tensor_into<T: TensorType, E>(
+    data: &mut [T],
+    read: impl FnOnce(*mut u8, u32) -> Result<(), E>,
+) -> Result<(), E> {
+    match T::bytes_mut(data) {
+        Some(buf) => read(buf.as_mut_ptr(), buf.len() as u32),
+        None => {
+            let mut buf = vec![0u8; mem::size_of_val(data)];
+            read(buf.as_mut_ptr(), buf.len() as u32)?;
+            for (d, v) in data.iter_mut().zip(T::from_bytes(&buf)) {
+                *d = v;
+            }
+            Ok(())
+        }
+    }
+}
+
+// Read an output tensor of `len` elements into the front of `buf` through
+// `read(buf, buf_max_size)`, and return `len`.
+pub(crate) fn read_output_into<T: TensorType, E>(
+    buf: &mut [T],
+    len: usize,
+    read: impl FnOnce(*mut u8, u32) -> Result<(), E>,
+) -> TensorResult<usize>
+where
+    Error: From<E>,
+{
+    if buf.len() < len {
+        return Err(Error::BufferTooSmall {
+            required: len,
+            actual: buf.len(),
+        });
+    }
+    read_tensor_into(&mut buf[..len], read)?;
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The `TF_DataType` values of `tensorflow/c/tf_datatype.h`, passed to the
+    // host as the `tensor_type` of `witx/wasmedge_tensorflow.witx`.
+    const TF_DATA_TYPES: [(&str, u32); 23] = [
+        ("float32", 1),
+        ("float64", 2),
+        ("int32", 3),
+        ("uint8", 4),
+        ("int16", 5),
+        ("int8", 6),
+        ("string", 7),
+        ("complex64", 8),
+        ("int64", 9),
+        ("bool", 10),
+        ("qint8", 11),
+        ("quint8", 12),
+        ("qint32", 13),
+        ("bfloat16", 14),
+        ("qint16", 15),
+        ("quint16", 16),
+        ("uint16", 17),
+        ("complex128", 18),
+        ("float16", 19),
+        ("resource", 20),
+        ("variant", 21),
+        ("uint32", 22),
+        ("uint64", 23),
+    ];
+
+    #[test]
+    fn values_match_tf_data_types() {
+        for (name, value) in TF_DATA_TYPES {
+            let data_type = DataType::from_value(value).unwrap();
+            assert_eq!(data_type.value(), value);
+            assert_eq!(data_type.name(), name);
+            assert_eq!(DataType::try_from(value), Ok(data_type));
+        }
+        assert_eq!(DataType::from_value(0), None);
+        assert_eq!(DataType::try_from(24), Err(24));
+        for data_type in [DataType::String, DataType::Resource, DataType::Variant] {
+            assert_eq!(data_type.size_of(), None);
+        }
+    }
+
+    // Check the data type of `T` and pass `values` through host bytes.
+    fn round_trip<T: TensorType + PartialEq + fmt::Debug>(values: &[T], data_type: DataType) {
+        assert_eq!(T::DATA_TYPE, data_type);
+        assert_eq!(data_type.size_of(), Some(mem::size_of::<T>()));
+        let bytes = T::as_bytes(values);
+        assert_eq!(bytes.len(), mem::size_of_val(values));
+        assert_eq!(T::from_bytes(bytes), values);
+        let read: Vec<T> = read_tensor(bytes.len(), |buf, buf_max_size| {
+            assert_eq!(buf_max_size as usize, bytes.len());
+            unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), buf, bytes.len()) };
+        });
+        assert_eq!(read, values);
+        if mem::size_of::<T>() > 1 {
+            // A trailing partial element is ignored.
+            let mut bytes = bytes.to_vec();
+            bytes.push(0xff);
+            assert_eq!(T::from_bytes(&bytes), values);
+        }
+    }
+
+    #[test]
+    fn element_round_trips() {
+        round_trip(&[1.5f32, -0.0, f32::MAX], DataType::Float);
+        round_trip(&[1.5f64, f64::MIN_POSITIVE], DataType::Double);
+        round_trip(&[i32::MIN, -1, 7], DataType::Int32);
+        round_trip(&[0u8, 255], DataType::UInt8);
+        round_trip(&[u16::MAX, 3], DataType::UInt16);
+        round_trip(&[u32::MAX, 3], DataType::UInt32);
+        round_trip(&[u64::MAX, 3], DataType::UInt64);
+        round_trip(&[i16::MIN, 3], DataType::Int16);
+        round_trip(&[i8::MIN, -1], DataType::Int8);
+        round_trip(&[i64::MIN, 3], DataType::Int64);
+        round_trip(&[true, false], DataType::Bool);
+        assert_eq!(f32::as_bytes(&[1.0]), 1.0f32.to_le_bytes());
+    }
+
+    #[test]
+    fn bool_bytes_are_normalized() {
+        assert_eq!(bool::from_bytes(&[0, 1, 2, 255]), [false, true, true, true]);
+        let mut values = [false; 2];
+        assert!(bool::bytes_mut(&mut values).is_none());
+    }
+
+    #[cfg(feature = "half")]
+    #[test]
+    fn half_round_trips() {
+        use half::{bf16, f16};
+        round_trip(
+            &[f16::from_f32(1.5), f16::MAX, f16::NEG_INFINITY],
+            DataType::Half,
+        );
+        round_trip(
+            &[bf16::from_f32(-2.0), bf16::MIN_POSITIVE],
+            DataType::BFloat16,
+        );
+    }
+
+    #[cfg(feature = "num-complex")]
+    #[test]
+    fn complex_round_trips() {
+        use num_complex::{Complex32, Complex64};
+        round_trip(&[Complex32::new(1.0, -2.0)], DataType::Complex64);
+        round_trip(&[Complex64::new(0.5, 3.0)], DataType::Complex128);
+        // The real part comes first, as in `std::complex`.
+        assert_eq!(
+            Complex32::as_bytes(&[Complex32::new(1.0, 0.0)])[..4],
+            1.0f32.to_le_bytes()
+        );
+    }
+
+    // A host writing `values` through the `get_tensor_data` arguments.
+    fn host(values: &[i32]) -> impl FnOnce(*mut u8, u32) -> Result<(), Error> + '_ {
+        |buf, buf_max_size| {
+            let bytes = i32::as_bytes(values);
+            assert_eq!(buf_max_size as usize, bytes.len());
+            unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), buf, bytes.len()) };
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn output_into_buffers() {
+        let mut buf = [0i32; 4];
+        assert!(matches!(
+            read_output_into(&mut buf, 3, host(&[1, 2, 3])),
+            Ok(3)
+        ));
+        assert_eq!(buf, [1, 2, 3, 0]);
+        assert!(matches!(read_output_into(&mut buf, 0, host(&[])), Ok(0)));
+        let failing = |_, _| Err(Error::TooLarge);
+        assert!(matches!(
+            read_output_into(&mut buf, 4, failing),
+            Err(Error::TooLarge)
+        ));
+    }
+
+    #[test]
+    fn undersized_output_buffer() {
+        let mut buf = [0i32; 2];
+        let unread = |_, _| -> Result<(), Error> { panic!("read into an undersized buffer") };
+        assert!(matches!(
+            read_output_into(&mut buf, 3, unread),
+            Err(Error::BufferTooSmall {
+                required: 3,
+                actual: 2
+            })
+        ));
+    }
+}
@@ -0,0 +1,161 @@
+//! Validation of input tensors before they are passed to the host.
+
+use crate::error::{Error, Result};
+use crate::DataType;
+
+// The type and shape a model expects for an input, such as a placeholder
+// reported by `tfinspect`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InputSpec {
+    pub dtype: DataType,
+    // Dimensions with -1 for dynamic sizes, or `None` when the rank is unknown.
+    pub shape: Option<Vec<i64>>,
+}
+
+impl InputSpec {
+    pub fn new(dtype: DataType, shape: &[i64]) -> InputSpec {
+        InputSpec {
+            dtype,
+            shape: Some(shape.to_vec()),
+        }
+    }
+
+    // A spec that only checks the element type.
+    pub fn with_any_shape(dtype: DataType) -> InputSpec {
+        InputSpec { dtype, shape: None }
+    }
+
+    // Check the type and the static dimensions of an input.
+    pub fn check(&self, dtype: DataType, shape: &[u64]) -> Result<()> {
+        if dtype != self.dtype {
+            return Err(Error::TypeMismatch {
+                expected: self.dtype,
+                actual: dtype,
+            });
+        }
+        let Some(dims) = &self.shape else {
+            return Ok(());
+        };
+        if dims.len() != shape.len() {
+            return Err(Error::RankMismatch {
+                expected: dims.len(),
+                actual: shape.len(),
+            });
+        }
+        for (index, (expected, actual)) in dims.iter().zip(shape).enumerate() {
+            if *expected >= 0 && *expected as u64 != *actual {
+                return Err(Error::DimMismatch {
+                    index,
+                    expected: *expected as u64,
+                    actual: *actual,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // Check the type and the element count of a TensorFlow-Lite input, which is
+    // passed without a shape. The count is only checked when every dimension
+    // is static.
+    pub fn check_len(&self, dtype: DataType, len: usize) -> Result<()> {
+        if dtype != self.dtype {
+            return Err(Error::TypeMismatch {
+                expected: self.dtype,
+                actual: dtype,
+            });
+        }
+        let Some(dims) = &self.shape else {
+            return Ok(());
+        };
+        if dims.iter().any(|d| *d < 0) {
+            return Ok(());
+        }
+        let count = dims
+            .iter()
+            .try_fold(1u64, |n, d| n.checked_mul(*d as u64))
+            .and_then(|n| usize::try_from(n).ok())
+            .ok_or(Error::TooLarge)?;
+        if count != len {
+            return Err(Error::LengthMismatch {
+                expected: count,
+                actual: len,
+            });
+        }
+        Ok(())
+    }
+}
+
+// Check that an input of `len` elements and `byte_len` bytes fits its shape and
+// the `i32` lengths of the host functions.
+pub(crate) fn check_input(name: &str, len: usize, byte_len: usize, shape: &[u64]) -> Result<()> {
+    let max = i32::MAX as usize;
+    if name.len() > max || shape.len() > max || byte_len > max {
+        return Err(Error::TooLarge);
+    }
+    // A scalar has an empty shape and one element; a zero dimension means no
+    // elements.
+    let count = shape
+        .iter()
+        .try_fold(1u64, |n, d| n.checked_mul(*d))
+        .and_then(|n| usize::try_from(n).ok())
+        .ok_or(Error::TooLarge)?;
+    if count != len {
+        return Err(Error::LengthMismatch {
+            expected: count,
+            actual: len,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dynamic_dimensions() {
+        let spec = InputSpec::new(DataType::Float, &[-1, 2, 3]);
+        assert!(spec.check(DataType::Float, &[4, 2, 3]).is_ok());
+        assert!(matches!(
+            spec.check(DataType::Float, &[4, 3, 3]),
+            Err(Error::DimMismatch {
+                index: 1,
+                expected: 2,
+                actual: 3
+            })
+        ));
+        assert!(matches!(
+            spec.check(DataType::Float, &[2, 3]),
+            Err(Error::RankMismatch { .. })
+        ));
+        // With a dynamic dimension, only the type is checked.
+        assert!(spec.check_len(DataType::Float, 7).is_ok());
+        assert!(matches!(
+            spec.check_len(DataType::UInt8, 6),
+            Err(Error::TypeMismatch {
+                expected: DataType::Float,
+                actual: DataType::UInt8
+            })
+        ));
+    }
+
+    #[test]
+    fn static_element_count() {
+        let spec = InputSpec::new(DataType::UInt8, &[1, 224, 224, 3]);
+        assert!(spec.check_len(DataType::UInt8, 150528).is_ok());
+        assert!(matches!(
+            spec.check_len(DataType::UInt8, 150527),
+            Err(Error::LengthMismatch {
+                expected: 150528,
+                actual: 150527
+            })
+        ));
+        assert!(InputSpec::with_any_shape(DataType::Int32)
+            .check_len(DataType::Int32, 3)
+            .is_ok());
+        assert!(matches!(
+            InputSpec::new(DataType::Int8, &[i64::MAX, 4]).check_len(DataType::Int8, 1),
+            Err(Error::TooLarge)
+        ));
+    }
+}
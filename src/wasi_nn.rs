@@ -0,0 +1,351 @@
+//! `TFLiteSession` on the WASI-NN interface.
+//!
+//! With the `wasi-nn` feature, `TFLiteSession` runs models through the
+//! `wasi_ephemeral_nn` host functions of the WasmEdge WASI-NN plug-in with its
+//! TensorFlow-Lite backend, instead of the WasmEdge-TensorFlowLite plug-in.
+//! The session API is unchanged, with these differences:
+//!
+//! * WASI-NN identifies tensors by index. Loading the session maps the names
+//!   of the model inputs and outputs to their index, reading them from the
+//!   TensorFlow-Lite model, and a name that is a number is used as the index.
+//!   `set_input_names` and `set_output_names` replace the mapping, in the
+//!   order of the model inputs and outputs. `add_input` panics and the output
+//!   functions fail for names that are not mapped, rather than guess an index
+//!   that may select the wrong tensor.
+//! * Inputs are passed with a flat shape, as the TensorFlow-Lite backend uses
+//!   the input shapes of the model. Element types without a WASI-NN tensor
+//!   type are passed as `u8` bytes.
+//! * WASI-NN reports neither output sizes nor shapes. Outputs are read into a
+//!   buffer grown until it holds the whole tensor, and `get_output_shape`
+//!   returns `Error::Unsupported`.
+//! * Runs are always blocking, and `run_async` runs the session before
+//!   returning.
+//! * `try_new` and `try_run` return the errors of the host, which `new` and
+//!   `run` panic with.
+//! * Dropping the session releases it with the `finalize_execution_context`
+//!   and `unload` host functions of WasmEdge 0.14 and later, ignoring their
+//!   errors.
+//!
+//! ```rust, ignore
+//! let mut session = TFLiteSession::new(&mod_buf);
+//! session.add_input("input", &flat_img).run();
+//! let res_vec: Vec<f32> = session.get_output("MobilenetV2/Predictions/Softmax");
+//! ```
+
+use crate::future::{AsyncRun, RunFuture};
+use crate::generated_nn as nn;
+use crate::inspect::tflite;
+use crate::validate::{self, InputSpec};
+use crate::{error, DataType, Error, OutputBuffer, TensorType};
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Mutex;
+
+// Initial size of the buffer of an output whose size is not known yet.
+const OUTPUT_BUF_INIT_SIZE: usize = 64 * 1024;
+
+// Output names and the last known byte sizes of the outputs, by index.
+#[derive(Default)]
+struct Outputs {
+    names: Vec<String>,
+    sizes: Vec<usize>,
+}
+
+// The TensorFlow-Lite session structure.
+pub struct TFLiteSession {
+    graph: nn::Graph,
+    context: nn::GraphExecutionContext,
+    inputs: Vec<String>,
+    // Expected types and shapes of the inputs, by name.
+    input_specs: HashMap<String, InputSpec>,
+    outputs: Mutex<Outputs>,
+}
+
+// Index of a tensor name: the name itself when it is a number, or its position
+// in the mapped names.
+fn tensor_index(names: &[String], name: &str) -> error::Result<u32> {
+    if let Ok(index) = name.parse() {
+        return Ok(index);
+    }
+    match names.iter().position(|n| n == name) {
+        Some(index) => Ok(index as u32),
+        None => Err(Error::UnmappedTensor),
+    }
+}
+
+// The WASI-NN tensor type of an element type.
+fn nn_tensor_type(data_type: DataType) -> nn::TensorType {
+    match data_type {
+        DataType::Half => nn::TENSOR_TYPE_F16,
+        DataType::Float => nn::TENSOR_TYPE_F32,
+        DataType::Double => nn::TENSOR_TYPE_F64,
+        DataType::Int32 => nn::TENSOR_TYPE_I32,
+        DataType::Int64 => nn::TENSOR_TYPE_I64,
+        _ => nn::TENSOR_TYPE_U8,
+    }
+}
+
+impl TFLiteSession {
+    // Panics if the host cannot load the model.
+    pub fn new<S: AsRef<[u8]>>(model_buf: S) -> TFLiteSession {
+        TFLiteSession::try_new(model_buf).unwrap_or_else(|e| panic!("load the model: {}", e))
+    }
+
+    // Load a model, returning the error of the host if it cannot be loaded.
+    pub fn try_new<S: AsRef<[u8]>>(model_buf: S) -> error::Result<TFLiteSession> {
+        // The host may load models the parser cannot read, which are then only
+        // addressed by index until their names are set.
+        let (inputs, outputs) = tflite::tensor_names(model_buf.as_ref()).unwrap_or_default();
+        unsafe {
+            let graph = nn::load(
+                &[model_buf.as_ref()],
+                nn::GRAPH_ENCODING_TENSORFLOWLITE,
+                nn::EXECUTION_TARGET_CPU,
+            )?;
+            let context = match nn::init_execution_context(graph) {
+                Ok(context) => context,
+                Err(e) => {
+                    let _ = nn::unload(graph);
+                    return Err(e.into());
+                }
+            };
+            Ok(TFLiteSession {
+                graph,
+                context,
+                inputs,
+                input_specs: HashMap::new(),
+                outputs: Mutex::new(Outputs {
+                    names: outputs,
+                    sizes: Vec::new(),
+                }),
+            })
+        }
+    }
+
+    // Create a session from a TensorFlow-Lite model file in a WASI-preopened path.
+    // WASI-NN only loads models from memory, so the file is read into the Wasm memory.
+    pub fn from_file(model_path: &str) -> TFLiteSession {
+        let model_buf =
+            std::fs::read(model_path).unwrap_or_else(|e| panic!("{}: {}", model_path, e));
+        TFLiteSession::new(model_buf)
+    }
+
+    // Name the inputs in the order of their indices, replacing the names of the model.
+    pub fn set_input_names(&mut self, names: &[&str]) -> &mut TFLiteSession {
+        self.inputs = names.iter().map(|n| n.to_string()).collect();
+        self
+    }
+
+    // Name the outputs in the order of their indices, replacing the names of the model.
+    pub fn set_output_names(&mut self, names: &[&str]) -> &mut TFLiteSession {
+        let outputs = self.outputs.get_mut().unwrap();
+        outputs.names = names.iter().map(|n| n.to_string()).collect();
+        self
+    }
+
+    // Add input name and input tensor into context.
+    // Panics if the name is not mapped to an index, or if the input does not fit
+    // the host functions or its `InputSpec`.
+    pub fn add_input<T: TensorType>(&mut self, name: &str, tensor_buf: &[T]) -> &mut TFLiteSession {
+        if let Err(e) = self.try_add_input(name, tensor_buf) {
+            panic!("add_input `{}`: {}", name, e);
+        }
+        self
+    }
+
+    // Add an input tensor after checking that its name is mapped to an index,
+    // that the lengths fit the host functions, and the `InputSpec` of the input
+    // if one is set.
+    pub fn try_add_input<T: TensorType>(
+        &mut self,
+        name: &str,
+        tensor_buf: &[T],
+    ) -> error::Result<&mut TFLiteSession> {
+        let index = tensor_index(&self.inputs, name)?;
+        let raw_buf = T::as_bytes(tensor_buf);
+        let len = tensor_buf.len();
+        validate::check_input(name, len, raw_buf.len(), &[len as u64])?;
+        if let Some(spec) = self.input_specs.get(name) {
+            spec.check_len(T::DATA_TYPE, len)?;
+        }
+        let tensor = nn::Tensor {
+            dimensions: &[len as u32],
+            data_type: nn_tensor_type(T::DATA_TYPE),
+            data: raw_buf,
+        };
+        unsafe {
+            nn::set_input(self.context, index, tensor)?;
+        }
+        Ok(self)
+    }
+
+    // Set the expected type and shape of an input, which `add_input` checks
+    // before passing the input to the host. Specs are never read from the model:
+    // inputs are only checked against the specs set here.
+    pub fn set_input_spec(&mut self, name: &str, spec: InputSpec) -> &mut TFLiteSession {
+        self.input_specs.insert(name.to_string(), spec);
+        self
+    }
+
+    // Run session.
+    // Panics if the run fails.
+    pub fn run(&mut self) -> &mut TFLiteSession {
+        if let Err(e) = self.try_run() {
+            panic!("run: {}", e);
+        }
+        self
+    }
+
+    // Run session, returning the error of the host if the run fails.
+    pub fn try_run(&mut self) -> error::Result<&mut TFLiteSession> {
+        unsafe {
+            nn::compute(self.context)?;
+        }
+        Ok(self)
+    }
+
+    // Read the bytes of an output tensor, growing the buffer until the output fits.
+    fn read_output(&self, name: &str) -> error::Result<Vec<u8>> {
+        let mut outputs = self.outputs.lock().unwrap();
+        let index = tensor_index(&outputs.names, name)?;
+        let known_size = outputs.sizes.get(index as usize).copied().unwrap_or(0);
+        // One more byte than the known size tells a complete read from a truncated one.
+        let size = match known_size {
+            0 => OUTPUT_BUF_INIT_SIZE,
+            n => n + 1,
+        };
+        let mut buf = vec![0u8; size];
+        loop {
+            let written = unsafe {
+                nn::get_output(self.context, index, buf.as_mut_ptr(), buf.len() as u32)? as usize
+            };
+            if written < buf.len() {
+                buf.truncate(written);
+                break;
+            }
+            buf.resize(buf.len() * 2, 0);
+        }
+        if outputs.sizes.len() <= index as usize {
+            outputs.sizes.resize(index as usize + 1, 0);
+        }
+        outputs.sizes[index as usize] = buf.len();
+        Ok(buf)
+    }
+
+    // Get output tensor data by name.
+    // Panics if the name is not mapped to an index or the host cannot read the
+    // output; `get_output_into` and `get_output_buffered` return the error instead.
+    pub fn get_output<T: TensorType>(&self, name: &str) -> Vec<T> {
+        match self.read_output(name) {
+            Ok(data) => T::from_bytes(&data),
+            Err(e) => panic!("get_output `{}`: {}", name, e),
+        }
+    }
+
+    // Get the element count of an output tensor, to size the buffer of `get_output_into`.
+    pub fn get_output_len<T: TensorType>(&self, name: &str) -> usize {
+        match self.read_output(name) {
+            Ok(data) => data.len() / mem::size_of::<T>(),
+            Err(e) => panic!("get_output_len `{}`: {}", name, e),
+        }
+    }
+
+    // Get output tensor data by name into a caller-provided buffer.
+    // Returns the number of elements written at the front of the buffer.
+    pub fn get_output_into<T: TensorType>(
+        &self,
+        name: &str,
+        buf: &mut [T],
+    ) -> error::Result<usize> {
+        let data = T::from_bytes(&self.read_output(name)?);
+        if buf.len() < data.len() {
+            return Err(Error::BufferTooSmall {
+                required: data.len(),
+                actual: buf.len(),
+            });
+        }
+        buf[..data.len()].copy_from_slice(&data);
+        Ok(data.len())
+    }
+
+    // Get output tensor data by name into a reusable buffer, which only reallocates
+    // when the output grows beyond its capacity.
+    pub fn get_output_buffered<'a, T: TensorType>(
+        &self,
+        name: &str,
+        buf: &'a mut OutputBuffer<T>,
+    ) -> error::Result<&'a [T]> {
+        let data = T::from_bytes(&self.read_output(name)?);
+        let out = buf.resize(data.len());
+        out.copy_from_slice(&data);
+        Ok(out)
+    }
+
+    // WASI-NN does not report output shapes.
+    pub fn get_output_shape(&self, _name: &str) -> error::Result<Vec<u64>> {
+        Err(Error::Unsupported("WASI-NN does not report output shapes"))
+    }
+
+    // Run session. WASI-NN has no background runs, so this completes the run
+    // before returning.
+    pub fn run_async(&mut self) -> RunFuture<'_, TFLiteSession> {
+        RunFuture::new(self)
+    }
+}
+
+impl AsyncRun for TFLiteSession {
+    fn run_blocking(&mut self) -> error::Result<()> {
+        self.try_run()?;
+        Ok(())
+    }
+}
+
+impl Drop for TFLiteSession {
+    // Releasing is best effort, as a drop must not panic.
+    fn drop(&mut self) {
+        unsafe {
+            let _ = nn::finalize_execution_context(self.context);
+            let _ = nn::unload(self.graph);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_need_a_mapping() {
+        let names = vec!["image".to_string(), "mask".to_string()];
+        assert!(matches!(tensor_index(&names, "mask"), Ok(1)));
+        assert!(matches!(tensor_index(&names, "3"), Ok(3)));
+        assert!(matches!(
+            tensor_index(&names, "input"),
+            Err(Error::UnmappedTensor)
+        ));
+        assert!(matches!(
+            tensor_index(&[], "input"),
+            Err(Error::UnmappedTensor)
+        ));
+    }
+
+    #[test]
+    fn element_types_map_to_wasi_nn_types() {
+        let types = [
+            (DataType::Half, "F16", 0),
+            (DataType::Float, "F32", 1),
+            (DataType::Double, "F64", 2),
+            (DataType::UInt8, "U8", 3),
+            (DataType::Int32, "I32", 4),
+            (DataType::Int64, "I64", 5),
+            // Types without a WASI-NN tensor type are passed as bytes.
+            (DataType::Int8, "U8", 3),
+            (DataType::Bool, "U8", 3),
+            (DataType::String, "U8", 3),
+        ];
+        for (dtype, name, raw) in types {
+            let nn_type = nn_tensor_type(dtype);
+            assert_eq!((nn_type.name(), nn_type.raw()), (name, raw), "{}", dtype);
+        }
+    }
+}